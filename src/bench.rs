@@ -0,0 +1,207 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use crate::diagnostics::Mode;
+use crate::error::Error;
+use crate::registry::Entry;
+use crate::solution::Part;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Phase {
+    Parse, Part(Part)
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part(part) => write!(f, "part{}", part)
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Format {
+    Table, Json, Csv
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format '{}', expected table, json or csv.", value))
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration
+}
+
+impl Stats {
+    /**
+    * Summarises the samples, sorting them in place. The median of an even number of samples is
+    * the mean of the middle two.
+    */
+    pub fn from_samples(samples: &mut [Duration]) -> Option<Stats> {
+        samples.sort();
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (*samples.get(middle.checked_sub(1)?)? + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+        Some(Stats { min: *samples.first()?, median, max: *samples.last()? })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Row {
+    pub day: u8,
+    pub phase: Phase,
+    pub runs: usize,
+    pub stats: Stats
+}
+
+/**
+* Parses and solves a day `runs` times, returning one row per phase in the order they ran.
+*/
+pub fn bench_day(entry: &Entry, text: &str, parts: &[Part], mode: Mode, runs: usize) -> Result<Vec<Row>, Error> {
+    let mut samples: Vec<(Phase, Vec<Duration>)> = Vec::new();
+    for _ in 0..runs {
+        for (phase, duration) in entry.solution.time(text, parts, mode)? {
+            match samples.iter_mut().find(|(it, _)| *it == phase) {
+                Some((_, durations)) => durations.push(duration),
+                None => samples.push((phase, vec!(duration)))
+            }
+        }
+    }
+    Ok(
+        samples.into_iter()
+            .filter_map(|(phase, mut durations)| Some(Row {
+                day: entry.day,
+                phase,
+                runs: durations.len(),
+                stats: Stats::from_samples(&mut durations)?
+            }))
+            .collect()
+    )
+}
+
+fn render_table(rows: &[Row]) -> String {
+    let mut table = format!(
+        "{:>3}  {:<6} {:>5} {:>12} {:>12} {:>12}\n",
+        "Day", "Phase", "Runs", "Min", "Median", "Max"
+    );
+    for row in rows {
+        table.push_str(&format!(
+            "{:>3}  {:<6} {:>5} {:>12} {:>12} {:>12}\n",
+            row.day,
+            row.phase.to_string(),
+            row.runs,
+            format!("{:.2?}", row.stats.min),
+            format!("{:.2?}", row.stats.median),
+            format!("{:.2?}", row.stats.max)
+        ));
+    }
+    table
+}
+
+fn render_json(rows: &[Row]) -> String {
+    let objects = rows.iter()
+        .map(|row| format!(
+            "  {{\"day\": {}, \"phase\": \"{}\", \"runs\": {}, \"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}",
+            row.day,
+            row.phase,
+            row.runs,
+            row.stats.min.as_nanos(),
+            row.stats.median.as_nanos(),
+            row.stats.max.as_nanos()
+        ))
+        .collect::<Vec<String>>();
+    if objects.is_empty() {
+        String::from("[]\n")
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+fn render_csv(rows: &[Row]) -> String {
+    let mut csv = String::from("day,phase,runs,min_ns,median_ns,max_ns\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.day,
+            row.phase,
+            row.runs,
+            row.stats.min.as_nanos(),
+            row.stats.median.as_nanos(),
+            row.stats.max.as_nanos()
+        ));
+    }
+    csv
+}
+
+/**
+* Durations are nanoseconds in JSON and CSV so that results can be compared between commits.
+*/
+pub fn render(rows: &[Row], format: Format) -> String {
+    match format {
+        Format::Table => render_table(rows),
+        Format::Json => render_json(rows),
+        Format::Csv => render_csv(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::*;
+    use crate::registry;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|it| Duration::from_micros(*it)).collect()
+    }
+
+    #[test]
+    fn should_summarise_samples() {
+        assert_eq!(
+            Stats::from_samples(&mut micros(&[9, 1, 5])),
+            Some(Stats { min: Duration::from_micros(1), median: Duration::from_micros(5), max: Duration::from_micros(9) })
+        );
+        assert_eq!(
+            Stats::from_samples(&mut micros(&[4, 1, 2, 9])).map(|it| it.median),
+            Some(Duration::from_micros(3))
+        );
+        assert_eq!(Stats::from_samples(&mut []), None)
+    }
+
+    #[test]
+    fn should_render_csv_in_nanoseconds() {
+        let rows = vec!(Row {
+            day: 1,
+            phase: Phase::Part(Part::Two),
+            runs: 3,
+            stats: Stats::from_samples(&mut micros(&[1, 2, 3])).unwrap()
+        });
+        assert_eq!(
+            render(&rows, Format::Csv),
+            "day,phase,runs,min_ns,median_ns,max_ns\n1,part2,3,1000,2000,3000\n"
+        )
+    }
+
+    #[test]
+    fn should_bench_every_phase() {
+        let rows = bench_day(registry::get(1).unwrap(), "1721\n979\n299\n", &[Part::One], Mode::Strict, 3).unwrap();
+        assert_eq!(
+            rows.iter().map(|it| (it.phase, it.runs)).collect::<Vec<(Phase, usize)>>(),
+            vec!((Phase::Parse, 3), (Phase::Part(Part::One), 3))
+        )
+    }
+}
@@ -0,0 +1,120 @@
+use std::cell::RefCell;
+use std::panic::resume_unwind;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::diagnostics::Mode;
+use crate::error::Error;
+use crate::registry::Solver;
+use crate::solution::{Answer, Part, Solved};
+
+thread_local! {
+    static CANCELLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/**
+* Whether the runner has given up on the part being solved on this thread. Loops that can run
+* for a long time on a bad input check this and return early; whatever they return is thrown
+* away. Always false outside `solve_within`.
+*/
+pub fn requested() -> bool {
+    CANCELLED.with(|cancelled| cancelled.borrow().as_ref().is_some_and(|it| it.load(Ordering::Relaxed)))
+}
+
+/**
+* Like `Day::solve`, but each part is parsed and solved on its own thread and given at most
+* `budget`. A part that runs over is answered as timed out and asked to stop, and the next part
+* starts without waiting for it. A part that never checks `requested` is left running on its
+* thread until it finishes or the process exits. A panic in a part is raised again on the
+* calling thread. The day's memory adds up the parts that finished, each having parsed the input
+* itself.
+*/
+pub fn solve_within(
+    day: Solver,
+    text: Arc<str>,
+    parts: &[Part],
+    mode: Mode,
+    budget: Duration
+) -> Result<Solved, Error> {
+    let mut solved = Solved { answers: Vec::new(), skipped: Vec::new(), memory: None };
+    for part in parts.iter().copied().filter(|part| day.parts().contains(part)) {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = channel();
+        let worker = {
+            let (day, text, cancelled) = (day.clone(), Arc::clone(&text), Arc::clone(&cancelled));
+            thread::spawn(move || {
+                CANCELLED.with(|it| *it.borrow_mut() = Some(cancelled));
+                let _ = sender.send(day.solve(&text, &[part], mode));
+            })
+        };
+        let start = Instant::now();
+        match receiver.recv_timeout(budget) {
+            Ok(result) => {
+                let part_solved = result?;
+                solved.answers.extend(part_solved.answers);
+                solved.skipped = part_solved.skipped;
+                solved.memory = match (solved.memory, part_solved.memory) {
+                    (Some(before), Some(memory)) => Some(before.followed_by(memory)),
+                    (before, memory) => before.or(memory)
+                };
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                cancelled.store(true, Ordering::Relaxed);
+                solved.answers.push(Answer { part, value: None, duration: start.elapsed(), timed_out: true, memory: None });
+            },
+            Err(RecvTimeoutError::Disconnected) => if let Err(payload) = worker.join() {
+                resume_unwind(payload)
+            }
+        }
+    }
+    Ok(solved)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cancel::*;
+    use crate::diagnostics::Diagnostics;
+    use crate::solution::Solution;
+
+    /**
+    * Part one returns at once, part two spins until it is cancelled.
+    */
+    struct Spin;
+
+    impl Solution for Spin {
+        type Input<'a> = ();
+        type PartOne = u32;
+        type PartTwo = u32;
+
+        fn parse<'a>(&self, _: impl Iterator<Item = &'a str>, _: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+            Ok(())
+        }
+
+        fn part_one(&self, _: &Self::Input<'_>) -> Option<u32> {
+            Some(1)
+        }
+
+        fn part_two(&self, _: &Self::Input<'_>) -> Option<u32> {
+            while !requested() {
+                thread::yield_now();
+            }
+            None
+        }
+    }
+
+    #[test]
+    fn should_time_out_slow_part_and_answer_the_rest() {
+        let solved = solve_within(Solver::Registered(&Spin), Arc::from(""), &[Part::Two, Part::One], Mode::Strict, Duration::from_millis(50))
+            .unwrap();
+        assert_eq!(
+            solved.answers.iter()
+                .map(|answer| (answer.part, answer.value.as_deref(), answer.timed_out))
+                .collect::<Vec<(Part, Option<&str>, bool)>>(),
+            vec!((Part::Two, None, true), (Part::One, Some("1"), false))
+        );
+        assert!(solved.answers[0].duration >= Duration::from_millis(50));
+        assert!(!requested())
+    }
+}
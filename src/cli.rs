@@ -0,0 +1,477 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use crate::bench::Format;
+use crate::diagnostics::Mode;
+use crate::generate::DEFAULT_SIZE;
+use crate::input::InputSource;
+use crate::report::Output;
+use crate::serve::DEFAULT_PORT;
+use crate::solution::Part;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Selection {
+    All, Day(u8)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct RunOptions {
+    pub selection: Selection,
+    pub part: Option<Part>,
+    pub input: Option<InputSource>,
+    pub assets: Option<PathBuf>,
+    pub mode: Mode,
+    pub output: Output,
+    pub jobs: Option<usize>,
+    pub timeout: Option<Duration>,
+    /**
+    * What day one's entries should add up to, and how many of them, when not the puzzle's.
+    */
+    pub target: Option<i128>,
+    pub count: Option<usize>
+}
+
+impl RunOptions {
+    pub fn new(selection: Selection) -> Self {
+        RunOptions {
+            selection,
+            part: None,
+            input: None,
+            assets: None,
+            mode: Mode::Lenient,
+            output: Output::Text,
+            jobs: None,
+            timeout: None,
+            target: None,
+            count: None
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct BenchOptions {
+    pub run: RunOptions,
+    pub runs: usize,
+    pub format: Format
+}
+
+impl BenchOptions {
+    pub fn new(selection: Selection) -> Self {
+        BenchOptions { run: RunOptions::new(selection), runs: 10, format: Format::Table }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct VerifyOptions {
+    pub run: RunOptions,
+    pub answers: Option<PathBuf>
+}
+
+/**
+* Without a seed, `generate` picks one from the clock and reports it so the input can be
+* written again.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct GenerateOptions {
+    pub day: u8,
+    pub seed: Option<u64>,
+    pub size: usize
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Command {
+    Run(RunOptions),
+    Bench(BenchOptions),
+    Verify(VerifyOptions),
+    Generate(GenerateOptions),
+    Serve { port: u16 },
+    List,
+    Help
+}
+
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let command = match args.next() {
+        Some(command) => command,
+        None => return Ok(Command::Help)
+    };
+    match command.as_str() {
+        "run" => parse_run(args),
+        "bench" => parse_bench(args),
+        "verify" => parse_verify(args),
+        "generate" => parse_generate(args),
+        "serve" => parse_serve(args),
+        "list" => expect_end(args, Command::List),
+        "help" | "-h" | "--help" => expect_end(args, Command::Help),
+        _ => Err(format!("Unknown command '{}'.", command))
+    }
+}
+
+fn parse_selection(args: &mut impl Iterator<Item = String>) -> Result<Selection, String> {
+    match args.next().as_deref() {
+        Some("all") => Ok(Selection::All),
+        Some(day) => Ok(Selection::Day(
+            u8::from_str(day).map_err(|_| format!("Invalid day '{}'.", day))?
+        )),
+        None => Err(String::from("Missing day, expected a number or 'all'."))
+    }
+}
+
+/**
+* Applies a flag shared by `run` and `bench`, returning false if `arg` is not one of them.
+*/
+fn parse_run_flag(arg: &str, args: &mut impl Iterator<Item = String>, options: &mut RunOptions) -> Result<bool, String> {
+    match arg {
+        "--part" | "-p" => {
+            options.part = Some(Part::from_str(&expect_value(args, arg)?)?);
+        },
+        "--input" | "-i" => {
+            options.input = Some(InputSource::from_arg(&expect_value(args, arg)?));
+        },
+        "--assets" => {
+            options.assets = Some(PathBuf::from(expect_value(args, arg)?));
+        },
+        "--strict" => options.mode = Mode::Strict,
+        _ => return Ok(false)
+    }
+    Ok(true)
+}
+
+/**
+* A time budget such as `500ms`, `2s`, `1.5m` or `10`, which is in seconds.
+*/
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    let (number, unit) = match value.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s")
+    };
+    let scale = match unit {
+        "ms" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        _ => 0.0
+    };
+    f64::from_str(number)
+        .ok()
+        .and_then(|it| Duration::try_from_secs_f64(it * scale).ok())
+        .filter(|it| !it.is_zero())
+        .ok_or(format!("Invalid timeout '{}', expected a positive time such as 500ms, 2s or 1m.", value))
+}
+
+fn check_run_options(options: &RunOptions) -> Result<(), String> {
+    if options.selection == Selection::All && options.input.is_some() {
+        return Err(String::from("--input needs a single day, use --assets to run all days."));
+    }
+    if (options.target.is_some() || options.count.is_some()) && options.selection != Selection::Day(1) {
+        return Err(String::from("--target and --count only apply to day 1."));
+    }
+    if options.count.is_some() && options.part == Some(Part::Two) {
+        return Err(String::from("--count answers a single search as part 1."));
+    }
+    Ok(())
+}
+
+fn parse_run(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = RunOptions::new(parse_selection(&mut args)?);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                options.output = Output::from_str(&expect_value(&mut args, &arg)?)?;
+            },
+            "--jobs" | "-j" => {
+                let jobs = expect_value(&mut args, &arg)?;
+                options.jobs = Some(
+                    usize::from_str(&jobs)
+                        .ok()
+                        .filter(|it| *it > 0)
+                        .ok_or(format!("Invalid number of jobs '{}', expected a positive number.", jobs))?
+                );
+            },
+            "--timeout" | "-t" => {
+                options.timeout = Some(parse_timeout(&expect_value(&mut args, &arg)?)?);
+            },
+            "--target" => {
+                let target = expect_value(&mut args, &arg)?;
+                options.target = Some(i128::from_str(&target).map_err(|_| format!("Invalid target '{}'.", target))?);
+            },
+            "--count" => {
+                let count = expect_value(&mut args, &arg)?;
+                options.count = Some(
+                    usize::from_str(&count)
+                        .ok()
+                        .filter(|it| *it > 0)
+                        .ok_or(format!("Invalid count '{}', expected a positive number.", count))?
+                );
+            },
+            _ => if !parse_run_flag(&arg, &mut args, &mut options)? {
+                return Err(format!("Unexpected argument '{}'.", arg));
+            }
+        }
+    }
+    check_run_options(&options)?;
+    Ok(Command::Run(options))
+}
+
+fn parse_bench(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = BenchOptions::new(parse_selection(&mut args)?);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" | "-n" => {
+                let runs = expect_value(&mut args, &arg)?;
+                options.runs = usize::from_str(&runs)
+                    .ok()
+                    .filter(|it| *it > 0)
+                    .ok_or(format!("Invalid number of runs '{}', expected a positive number.", runs))?;
+            },
+            "--format" | "-f" => {
+                options.format = Format::from_str(&expect_value(&mut args, &arg)?)?;
+            },
+            _ => if !parse_run_flag(&arg, &mut args, &mut options.run)? {
+                return Err(format!("Unexpected argument '{}'.", arg));
+            }
+        }
+    }
+    check_run_options(&options.run)?;
+    Ok(Command::Bench(options))
+}
+
+/**
+* `verify` checks every day unless a day is given.
+*/
+fn parse_verify(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.peekable();
+    let selection = match args.peek() {
+        Some(arg) if !arg.starts_with('-') => parse_selection(&mut args)?,
+        _ => Selection::All
+    };
+    let mut options = VerifyOptions { run: RunOptions::new(selection), answers: None };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--answers" => {
+                options.answers = Some(PathBuf::from(expect_value(&mut args, &arg)?));
+            },
+            "--timeout" | "-t" => {
+                options.run.timeout = Some(parse_timeout(&expect_value(&mut args, &arg)?)?);
+            },
+            _ => if !parse_run_flag(&arg, &mut args, &mut options.run)? {
+                return Err(format!("Unexpected argument '{}'.", arg));
+            }
+        }
+    }
+    check_run_options(&options.run)?;
+    Ok(Command::Verify(options))
+}
+
+fn parse_generate(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let day = match parse_selection(&mut args)? {
+        Selection::Day(day) => day,
+        Selection::All => return Err(String::from("generate needs a single day."))
+    };
+    let mut options = GenerateOptions { day, seed: None, size: DEFAULT_SIZE };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" | "-s" => {
+                let seed = expect_value(&mut args, &arg)?;
+                options.seed = Some(
+                    u64::from_str(&seed).map_err(|_| format!("Invalid seed '{}', expected a number.", seed))?
+                );
+            },
+            "--size" => {
+                let size = expect_value(&mut args, &arg)?;
+                options.size = usize::from_str(&size)
+                    .ok()
+                    .filter(|it| *it > 0)
+                    .ok_or(format!("Invalid size '{}', expected a positive number.", size))?;
+            },
+            _ => return Err(format!("Unexpected argument '{}'.", arg))
+        }
+    }
+    Ok(Command::Generate(options))
+}
+
+fn parse_serve(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut port = DEFAULT_PORT;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                let value = expect_value(&mut args, &arg)?;
+                port = u16::from_str(&value).map_err(|_| format!("Invalid port '{}'.", value))?;
+            },
+            _ => return Err(format!("Unexpected argument '{}'.", arg))
+        }
+    }
+    Ok(Command::Serve { port })
+}
+
+fn expect_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next().ok_or(format!("Missing value for {}.", flag))
+}
+
+fn expect_end(mut args: impl Iterator<Item = String>, command: Command) -> Result<Command, String> {
+    match args.next() {
+        Some(arg) => Err(format!("Unexpected argument '{}'.", arg)),
+        None => Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| String::from(*arg)))
+    }
+
+    #[test]
+    fn should_parse_run_with_part() {
+        assert_eq!(
+            parse(&["run", "7", "--part", "2"]),
+            Ok(Command::Run(RunOptions { part: Some(Part::Two), ..RunOptions::new(Selection::Day(7)) }))
+        );
+        assert_eq!(
+            parse(&["run", "all"]),
+            Ok(Command::Run(RunOptions::new(Selection::All)))
+        )
+    }
+
+    #[test]
+    fn should_parse_run_with_input_sources() {
+        assert_eq!(
+            parse(&["run", "7", "--input", "-"]),
+            Ok(Command::Run(RunOptions { input: Some(InputSource::Stdin), ..RunOptions::new(Selection::Day(7)) }))
+        );
+        assert_eq!(
+            parse(&["run", "all", "--assets", "/tmp/inputs"]),
+            Ok(Command::Run(RunOptions { assets: Some(PathBuf::from("/tmp/inputs")), ..RunOptions::new(Selection::All) }))
+        );
+        assert!(parse(&["run", "all", "--input", "day_seven"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_strict_mode() {
+        assert_eq!(
+            parse(&["run", "2", "--strict"]),
+            Ok(Command::Run(RunOptions { mode: Mode::Strict, ..RunOptions::new(Selection::Day(2)) }))
+        )
+    }
+
+    #[test]
+    fn should_parse_json_output() {
+        assert_eq!(
+            parse(&["run", "all", "--format", "json"]),
+            Ok(Command::Run(RunOptions { output: Output::Json, ..RunOptions::new(Selection::All) }))
+        );
+        assert!(parse(&["run", "all", "--format", "csv"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_jobs() {
+        assert_eq!(
+            parse(&["run", "all", "--jobs", "4"]),
+            Ok(Command::Run(RunOptions { jobs: Some(4), ..RunOptions::new(Selection::All) }))
+        );
+        assert!(parse(&["run", "all", "-j", "0"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_timeout() {
+        let timeout = |value: &str| match parse(&["run", "all", "--timeout", value]) {
+            Ok(Command::Run(options)) => options.timeout,
+            _ => None
+        };
+        assert_eq!(timeout("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(timeout("2s"), Some(Duration::from_secs(2)));
+        assert_eq!(timeout("1.5m"), Some(Duration::from_secs(90)));
+        assert_eq!(timeout("10"), Some(Duration::from_secs(10)));
+        assert_eq!(
+            parse(&["verify", "-t", "3s"]),
+            Ok(Command::Verify(VerifyOptions {
+                run: RunOptions { timeout: Some(Duration::from_secs(3)), ..RunOptions::new(Selection::All) },
+                answers: None
+            }))
+        );
+        for invalid in ["0", "-1s", "2h", "soon", "inf"] {
+            assert!(parse(&["run", "all", "--timeout", invalid]).is_err(), "{}", invalid);
+        }
+        assert!(parse(&["bench", "all", "--timeout", "1s"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_target_and_count_for_day_one() {
+        assert_eq!(
+            parse(&["run", "1", "--target", "-150", "--count", "4"]),
+            Ok(Command::Run(RunOptions { target: Some(-150), count: Some(4), ..RunOptions::new(Selection::Day(1)) }))
+        );
+        assert!(parse(&["run", "2", "--target", "150"]).is_err());
+        assert!(parse(&["run", "all", "--count", "4"]).is_err());
+        assert!(parse(&["run", "1", "--count", "0"]).is_err());
+        assert!(parse(&["run", "1", "--count", "4", "--part", "2"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_bench() {
+        assert_eq!(
+            parse(&["bench", "15", "--runs", "3", "--format", "csv", "--part", "2"]),
+            Ok(Command::Bench(BenchOptions {
+                run: RunOptions { part: Some(Part::Two), ..RunOptions::new(Selection::Day(15)) },
+                runs: 3,
+                format: Format::Csv
+            }))
+        );
+        assert_eq!(parse(&["bench", "all"]), Ok(Command::Bench(BenchOptions::new(Selection::All))));
+        assert!(parse(&["bench", "1", "--runs", "0"]).is_err());
+        assert!(parse(&["bench", "1", "--format", "xml"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_verify() {
+        assert_eq!(
+            parse(&["verify"]),
+            Ok(Command::Verify(VerifyOptions { run: RunOptions::new(Selection::All), answers: None }))
+        );
+        assert_eq!(
+            parse(&["verify", "10", "--answers", "my_answers"]),
+            Ok(Command::Verify(VerifyOptions {
+                run: RunOptions::new(Selection::Day(10)),
+                answers: Some(PathBuf::from("my_answers"))
+            }))
+        )
+    }
+
+    #[test]
+    fn should_parse_generate() {
+        assert_eq!(
+            parse(&["generate", "16", "--seed", "42", "--size", "30"]),
+            Ok(Command::Generate(GenerateOptions { day: 16, seed: Some(42), size: 30 }))
+        );
+        assert_eq!(
+            parse(&["generate", "3"]),
+            Ok(Command::Generate(GenerateOptions { day: 3, seed: None, size: DEFAULT_SIZE }))
+        );
+        assert!(parse(&["generate", "all"]).is_err());
+        assert!(parse(&["generate", "3", "--size", "0"]).is_err());
+        assert!(parse(&["generate", "3", "--seed", "-1"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_serve() {
+        assert_eq!(parse(&["serve"]), Ok(Command::Serve { port: DEFAULT_PORT }));
+        assert_eq!(parse(&["serve", "--port", "8080"]), Ok(Command::Serve { port: 8080 }));
+        assert!(parse(&["serve", "--port", "70000"]).is_err())
+    }
+
+    #[test]
+    fn should_parse_list_and_help() {
+        assert_eq!(parse(&["list"]), Ok(Command::List));
+        assert_eq!(parse(&[]), Ok(Command::Help));
+        assert_eq!(parse(&["--help"]), Ok(Command::Help))
+    }
+
+    #[test]
+    fn should_reject_bad_arguments() {
+        assert!(parse(&["run"]).is_err());
+        assert!(parse(&["run", "seven"]).is_err());
+        assert!(parse(&["run", "7", "--part", "3"]).is_err());
+        assert!(parse(&["run", "7", "--part"]).is_err());
+        assert!(parse(&["fly"]).is_err())
+    }
+}
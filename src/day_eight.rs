@@ -1,19 +1,41 @@
-use crate::file_util::read_non_blank_lines;
+use crate::cancel;
+use crate::file_util::numbered_non_blank_lines;
 use crate::day_eight::Instruction::{NOP, JMP, ACC};
 use std::str::FromStr;
 use std::collections::HashSet;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::{Part, Solution, BOTH_PARTS};
 
-enum Instruction {
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone)]
+pub enum Instruction {
     NOP(isize),
     ACC(isize),
     JMP(isize)
 }
 
-fn parse_isize_from_line(line: &str) -> Option<isize> {
-    isize::from_str(&line[4..]).ok()
+/**
+* Parses an instruction such as `jmp +4`.
+*/
+pub fn parse_instruction(number: usize, line: &str) -> Result<Instruction, Diagnostic> {
+    let (operation, argument) = line.split_once(' ')
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected an operation and an argument"))?;
+    let amount = isize::from_str(argument)
+        .map_err(|error| Diagnostic::at(number, line, argument, error))?;
+    match operation {
+        "nop" => Ok(NOP(amount)),
+        "acc" => Ok(ACC(amount)),
+        "jmp" => Ok(JMP(amount)),
+        _ => Err(Diagnostic::at(number, line, operation, "unknown operation, expected nop, acc or jmp"))
+    }
 }
 
-fn get_end_state(instructions: &[Instruction]) -> (usize, isize) {
+/**
+* Runs the program until an instruction repeats or it terminates, returning the
+* instruction it stopped at and the accumulator.
+*/
+pub fn get_end_state(instructions: &[Instruction]) -> (usize, isize) {
     let mut visited = HashSet::new();
     let mut current_instruction = 0;
     let mut sum = 0_isize;
@@ -36,8 +58,15 @@ fn get_end_state(instructions: &[Instruction]) -> (usize, isize) {
     (current_instruction, sum)
 }
 
-fn get_bug_free_result(instructions: &mut Vec<Instruction>) -> Option<isize> {
+/**
+* Swaps one `nop` or `jmp` so that the program terminates, returning the accumulator. Gives up
+* if cancelled.
+*/
+pub fn get_bug_free_result(instructions: &mut [Instruction]) -> Option<isize> {
     for count in 0..instructions.len() {
+        if cancel::requested() {
+            return None;
+        }
         let replacement = match instructions[count] {
             NOP(x) => Some(JMP(x)),
             JMP(x) => Some(NOP(x)),
@@ -45,7 +74,7 @@ fn get_bug_free_result(instructions: &mut Vec<Instruction>) -> Option<isize> {
         };
         if let Some(replace) = replacement {
             let current = std::mem::replace(&mut instructions[count], replace);
-            let result = get_end_state(&instructions);
+            let result = get_end_state(instructions);
             instructions[count] = current;
 
             if result.0 == instructions.len() {
@@ -56,26 +85,71 @@ fn get_bug_free_result(instructions: &mut Vec<Instruction>) -> Option<isize> {
     None
 }
 
-#[allow(dead_code)]
-pub fn run_day_eight() {
-    let mut instructions = read_non_blank_lines("assets/day_eight")
-        .filter_map(|line| match &line[..=2] {
-            "nop" => Some(NOP(parse_isize_from_line(&line)?)),
-            "acc" => Some(ACC(parse_isize_from_line(&line)?)),
-            "jmp" => Some(JMP(parse_isize_from_line(&line)?)),
-            _ => None
-        })
-        .collect::<Vec<Instruction>>();
-    let result = get_end_state(&instructions);
-    let result2 = get_bug_free_result(&mut instructions);
-
-    println!(
-        "Loop found at {} with sum {}.",
-        result.0,
-        result.1
-    );
-    if let Some(fixed_result) = result2 {
-        println!("After fixing we have {}.", fixed_result)
+/**
+* Reference run: steps through the program until it revisits an instruction or leaves it,
+* returning whether it ended just past the last instruction, and the accumulator.
+*/
+pub fn run_naively(instructions: &[Instruction]) -> (bool, isize) {
+    let mut visited = vec!(false; instructions.len());
+    let (mut current, mut sum) = (0_isize, 0_isize);
+    while current >= 0 && (current as usize) < instructions.len() && !visited[current as usize] {
+        visited[current as usize] = true;
+        match instructions[current as usize] {
+            ACC(amount) => {
+                sum += amount;
+                current += 1;
+            },
+            JMP(amount) => current += amount,
+            NOP(_) => current += 1
+        }
+    }
+    (current as usize == instructions.len(), sum)
+}
+
+pub struct DayEight;
+
+impl Solution for DayEight {
+    type Input<'a> = Vec<Instruction>;
+    type PartOne = isize;
+    type PartTwo = isize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(
+            numbered_non_blank_lines(lines)
+                .filter_map(|(number, line)| diagnostics.check(parse_instruction(number, line)))
+                .collect()
+        )
+    }
+
+    fn part_one(&self, instructions: &Self::Input<'_>) -> Option<isize> {
+        Some(get_end_state(instructions).1)
+    }
+
+    fn part_two(&self, instructions: &Self::Input<'_>) -> Option<isize> {
+        get_bug_free_result(&mut instructions.clone())
+    }
+
+    fn reference_parts(&self) -> &'static [Part] {
+        BOTH_PARTS
+    }
+
+    fn reference_part_one(&self, instructions: &Self::Input<'_>) -> Option<isize> {
+        Some(run_naively(instructions).1)
+    }
+
+    fn reference_part_two(&self, instructions: &Self::Input<'_>) -> Option<isize> {
+        (0..instructions.len())
+            .filter_map(|index| {
+                let mut program = instructions.clone();
+                program[index] = match program[index] {
+                    NOP(amount) => JMP(amount),
+                    JMP(amount) => NOP(amount),
+                    ACC(_) => return None
+                };
+                Some(run_naively(&program))
+            })
+            .find(|(terminated, _)| *terminated)
+            .map(|(_, sum)| sum)
     }
 }
 
@@ -96,6 +170,14 @@ mod tests {
         assert_eq!(get_end_state(&under_test), (4, 12))
     }
 
+    #[test]
+    fn should_point_at_bad_instruction() {
+        let diagnostic = parse_instruction(2, "jmp +x4").err().unwrap();
+        assert_eq!((diagnostic.column, diagnostic.width), (5, 3));
+        let diagnostic = parse_instruction(3, "hop +1").err().unwrap();
+        assert_eq!((diagnostic.column, diagnostic.width), (1, 3))
+    }
+
     #[test]
     fn should_find_the_sum_of_the_correct_program() {
         let mut under_test = vec!(
@@ -1,53 +1,91 @@
-use crate::file_util::read_non_blank_lines;
-use std::str::{Chars, FromStr};
-use std::iter::{once};
-use itertools::Itertools;
+use crate::file_util::numbered_non_blank_lines;
+use std::str::FromStr;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::Solution;
 use crate::day_eighteen::Token::{Number, LeftParen, RightParen, Operation};
 use crate::day_eighteen::OperationType::{Multiply, Add};
 
 #[derive(Eq, PartialEq, Debug, Clone)]
-enum Token { LeftParen, RightParen, Operation(OperationType), Number(usize) }
+pub enum Token { LeftParen, RightParen, Operation(OperationType), Number(usize) }
 #[derive(Eq, PartialEq, Debug, Clone)]
-enum OperationType {
+pub enum OperationType {
     Multiply, Add
 }
-#[derive(Eq, PartialEq)]
-enum OperationPrecedence {
+/**
+* `None` evaluates left to right, `AddWins` evaluates addition before multiplication.
+*/
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum OperationPrecedence {
     None,
     AddWins
 }
 
 impl OperationType {
-    fn apply(&self, first: usize, second: usize) -> usize {
+    /**
+    * The result, or `None` if it does not fit.
+    */
+    pub fn apply(&self, first: usize, second: usize) -> Option<usize> {
         match self {
-            Multiply => first * second,
-            Add => first + second
+            Multiply => first.checked_mul(second),
+            Add => first.checked_add(second)
         }
     }
 }
 
-fn to_tokens(chars: &mut Chars) -> Vec<Token> {
-    let mut buff = Vec::new();
-    while let Some(x) = chars.next() {
-        match x {
-            '(' => buff.push(LeftParen),
-            ')' => buff.push(RightParen),
-            '+' => buff.push(Operation(Add)),
-            '*' => buff.push(Operation(Multiply)),
-            _ => {
-                if let Ok(v) = usize::from_str(
-                    &once(x)
-                        .chain(chars.by_ref().peeking_take_while(|x| x.is_ascii_digit()))
-                        .join("")
-                        .trim()
-                ) { buff.push(Number(v)) };
-            }
-        }
+/**
+* Tokenises an expression such as `2 * (3 + 4)`, ignoring whitespace, and checks that numbers
+* and operators alternate and that the brackets balance.
+*/
+pub fn parse_expression(number: usize, line: &str) -> Result<Vec<Token>, Diagnostic> {
+    let mut tokens = Vec::new();
+    let mut depth = 0_usize;
+    let mut expect_operand = true;
+    let mut characters = line.char_indices().peekable();
+    while let Some((offset, character)) = characters.next() {
+        let token = match character {
+            _ if character.is_whitespace() => continue,
+            '(' if expect_operand => {
+                depth += 1;
+                LeftParen
+            },
+            ')' if !expect_operand && depth > 0 => {
+                depth -= 1;
+                RightParen
+            },
+            '+' if !expect_operand => Operation(Add),
+            '*' if !expect_operand => Operation(Multiply),
+            _ if character.is_ascii_digit() && expect_operand => {
+                let mut end = offset + 1;
+                while let Some((next, _)) = characters.next_if(|(_, next)| next.is_ascii_digit()) {
+                    end = next + 1;
+                }
+                let digits = &line[offset..end];
+                Number(usize::from_str(digits).map_err(|error| Diagnostic::at(number, line, digits, error))?)
+            },
+            _ => return Err(Diagnostic::at(
+                number,
+                line,
+                &line[offset..offset + character.len_utf8()],
+                if expect_operand { "expected a number or '('" } else { "expected '+', '*' or ')'" }
+            ))
+        };
+        expect_operand = matches!(token, LeftParen | Operation(_));
+        tokens.push(token);
+    }
+    if expect_operand {
+        return Err(Diagnostic::at_end(number, line, "expected a number or '('"));
+    }
+    if depth > 0 {
+        return Err(Diagnostic::at_end(number, line, "expected ')'"));
     }
-    buff
+    Ok(tokens)
 }
 
-fn convert_to_post_fix(tokens: &[Token], precedence: OperationPrecedence) -> Vec<Token> {
+/**
+* Reorders the tokens into postfix with the given precedence.
+*/
+pub fn convert_to_post_fix(tokens: &[Token], precedence: OperationPrecedence) -> Vec<Token> {
     let mut stack = Vec::new();
     let mut post_fix = Vec::new();
     for token in tokens.iter() {
@@ -83,14 +121,17 @@ fn convert_to_post_fix(tokens: &[Token], precedence: OperationPrecedence) -> Vec
     post_fix
 }
 
-fn solve(tokens: &[Token]) -> Option<usize> {
+/**
+* Evaluates a postfix expression, or gives `None` if a step overflows.
+*/
+pub fn solve(tokens: &[Token]) -> Option<usize> {
     let mut buff = Vec::new();
     for token in tokens.iter() {
         match token {
             Operation(op_type) => {
                 if let Some(Number(last)) = buff.pop() {
                     if let Some(Number(second_last)) = buff.pop() {
-                        buff.push(Number(op_type.apply(second_last, last)));
+                        buff.push(Number(op_type.apply(second_last, last)?));
                     }
                 }
             },
@@ -104,19 +145,61 @@ fn solve(tokens: &[Token]) -> Option<usize> {
     }
 }
 
-#[allow(dead_code)]
-pub fn run_day_eighteen() {
-    let result: (usize, usize) = read_non_blank_lines("assets/day_eighteen")
-        .map(|x| to_tokens(&mut x.chars()))
-        .filter_map(|x|
-             Some(
-                (
-                    solve(&convert_to_post_fix(&x, OperationPrecedence::None))?,
-                    solve(&convert_to_post_fix(&x, OperationPrecedence::AddWins))?
-                )
-             )
+pub struct DayEighteen;
+
+impl Solution for DayEighteen {
+    type Input<'a> = Vec<Vec<Token>>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(
+            numbered_non_blank_lines(lines)
+                .filter_map(|(number, line)| diagnostics.check(parse_expression(number, line)))
+                .collect()
         )
-        .fold((0, 0), |prev, (first, second)| (prev.0 + first, prev.1 + second));
+    }
 
-    println!("Part 1 {} and Part 2 {}", result.0, result.1)
+    fn part_one(&self, expressions: &Self::Input<'_>) -> Option<usize> {
+        sum_results(expressions, OperationPrecedence::None)
+    }
+
+    fn part_two(&self, expressions: &Self::Input<'_>) -> Option<usize> {
+        sum_results(expressions, OperationPrecedence::AddWins)
+    }
+}
+
+/**
+* Adds up every expression evaluated with `precedence`, or gives `None` if anything overflows.
+*/
+fn sum_results(expressions: &[Vec<Token>], precedence: OperationPrecedence) -> Option<usize> {
+    expressions.iter()
+        .try_fold(0_usize, |sum, expression| {
+            sum.checked_add(solve(&convert_to_post_fix(expression, precedence))?)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_eighteen::*;
+
+    #[test]
+    fn should_evaluate_with_either_precedence() {
+        let expressions = vec!(
+            parse_expression(1, "2 * 3 + (4 * 5)").unwrap(),
+            parse_expression(2, "1 + (2 * 3) + (4 * (5 + 6))").unwrap()
+        );
+        assert_eq!((DayEighteen.part_one(&expressions), DayEighteen.part_two(&expressions)), (Some(77), Some(97)))
+    }
+
+    #[test]
+    fn should_point_at_malformed_expressions() {
+        let rejected = vec!("2 * x", "(1 + 2", "3 +", "1 2", "4)")
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, line)| parse_expression(index + 1, line).err())
+            .map(|diagnostic| (diagnostic.line, diagnostic.column))
+            .collect::<Vec<(usize, usize)>>();
+        assert_eq!(rejected, vec!((1, 5), (2, 7), (3, 4), (4, 3), (5, 2)))
+    }
 }
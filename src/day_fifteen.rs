@@ -1,28 +1,70 @@
 use std::collections::HashMap;
+use std::str::FromStr;
+use crate::cancel;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::file_util::numbered_non_blank_lines;
+use crate::error::Error;
+use crate::solution::Solution;
 
-const PUZZLE_INPUT: [usize; 6] = [2, 0, 1, 9, 5, 19];
+pub struct DayFifteen;
 
-#[allow(dead_code)]
-pub fn run_day_fifteen() {
-    let last_spoken = solve_game(&PUZZLE_INPUT, 2020);
-    let last_spoken_two = solve_game(&PUZZLE_INPUT, 30000000);
-    println!("Task 1 {} and Task 2 {}", last_spoken, last_spoken_two);
+impl Solution for DayFifteen {
+    type Input<'a> = Vec<usize>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(
+            numbered_non_blank_lines(lines)
+                .filter_map(|(number, line)| diagnostics.check(parse_starting_numbers(number, line)))
+                .flatten()
+                .collect()
+        )
+    }
+
+    fn part_one(&self, input: &Self::Input<'_>) -> Option<usize> {
+        solve_game(input, 2020)
+    }
+
+    fn part_two(&self, input: &Self::Input<'_>) -> Option<usize> {
+        solve_game(input, 30000000)
+    }
+}
+
+/**
+* Parses a line of starting numbers such as `0,3,6`.
+*/
+fn parse_starting_numbers(number: usize, line: &str) -> Result<Vec<usize>, Diagnostic> {
+    line.split(',')
+        .map(str::trim)
+        .map(|field| usize::from_str(field).map_err(|error| Diagnostic::at(number, line, field, error)))
+        .collect()
 }
 
-fn solve_game(input: &[usize], length: usize) -> usize {
-    let mut spoken: HashMap<usize, usize> = (&input[0..input.len()])
+/**
+* Plays the memory game from the starting numbers, returning the number spoken
+* on turn `length`, or `None` if there are no starting numbers or the game is cancelled.
+*/
+pub fn solve_game(input: &[usize], length: usize) -> Option<usize> {
+    let last_starting = *input.last()?;
+    let mut spoken: HashMap<usize, usize> = input[0..input.len()]
         .iter()
         .copied()
         .enumerate()
         .map(|(i, v)| (v, i + 1))
         .collect();
 
-    (input.len()+1..=length).fold(
-        input.last().copied().unwrap(),
+    (input.len()+1..=length).try_fold(
+        last_starting,
         |last_spoken, current| {
-            spoken.insert(last_spoken, current - 1)
-                .map(|last| current - last - 1)
-                .unwrap_or(0)
+            if current % 65536 == 0 && cancel::requested() {
+                return None;
+            }
+            Some(
+                spoken.insert(last_spoken, current - 1)
+                    .map(|last| current - last - 1)
+                    .unwrap_or(0)
+            )
         }
     )
 }
@@ -33,8 +75,27 @@ mod tests {
 
     #[test]
     fn should_solve_part_one() {
-        assert_eq!(solve_game(&[0,3,6], 2020), 436);
-        assert_eq!(solve_game(&[2,1,3], 2020), 10);
-        assert_eq!(solve_game(&[1,3,2], 2020), 1);
+        assert_eq!(solve_game(&[0,3,6], 2020), Some(436));
+        assert_eq!(solve_game(&[2,1,3], 2020), Some(10));
+        assert_eq!(solve_game(&[1,3,2], 2020), Some(1));
+    }
+
+    #[test]
+    fn should_not_answer_without_starting_numbers() {
+        let numbers = DayFifteen.parse(vec!("", "  ").into_iter(), &mut Diagnostics::new()).unwrap();
+        assert_eq!((DayFifteen.part_one(&numbers), DayFifteen.part_two(&numbers)), (None, None))
+    }
+
+    #[test]
+    fn should_reject_lines_with_bad_numbers() {
+        let mut diagnostics = Diagnostics::new();
+        let numbers = DayFifteen.parse(vec!("1,x,3", "0,3,6").into_iter(), &mut diagnostics).unwrap();
+        assert_eq!(numbers, vec!(0, 3, 6));
+        assert_eq!(
+            diagnostics.rejected().iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column))
+                .collect::<Vec<(usize, usize)>>(),
+            vec!((1, 3))
+        )
     }
 }
@@ -1,30 +1,72 @@
-use crate::file_util::read_lines;
 use std::num::ParseIntError;
 use itertools::Itertools;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::file_util::numbered_non_blank_lines;
+use crate::error::Error;
+use crate::solution::Solution;
 
-fn to_integer_from_binary_string(binary_str: &str, ones_char: char) -> Result<u32, ParseIntError> {
+/**
+* Reads `binary_str` as binary, with `ones_char` as one and anything else as zero.
+*/
+pub fn to_integer_from_binary_string(binary_str: &str, ones_char: char) -> Result<u32, ParseIntError> {
     let binary_representation: String = binary_str.chars()
         .map(|curr| if curr == ones_char { '1' } else { '0' })
         .collect();
     u32::from_str_radix(binary_representation.as_str(), 2)
 }
 
-#[allow(dead_code)]
-pub fn run_day_five() {
-    let converted: Vec<u32> = read_lines("assets/day_five")
-        .filter_map(|line| {
-            let row = to_integer_from_binary_string(&line[..=6], 'B').ok()?;
-            let seat = to_integer_from_binary_string(&line[7..=9], 'R').ok()?;
-            Some(8 * row + seat)
-        })
-        .sorted()
-        .collect();
-    let task_two = converted.windows(2).find(|seats| {
-        seats[0] + 1 != seats[1] && seats[1] - 2 == seats[0]
-    })
-        .map(|result| result[0] + 1);
+/**
+* A boarding pass is seven F or B characters for the row followed by three L or R characters
+* for the column. Returns the seat id.
+*/
+pub fn parse_seat(number: usize, line: &str) -> Result<u32, Diagnostic> {
+    let mut letters = line.char_indices();
+    for (index, (offset, letter)) in letters.by_ref().take(10).enumerate() {
+        let (low, high) = if index < 7 { ('F', 'B') } else { ('L', 'R') };
+        if letter != low && letter != high {
+            let fragment = &line[offset..offset + letter.len_utf8()];
+            return Err(Diagnostic::at(number, line, fragment, format!("expected {} or {}", low, high)));
+        }
+    }
+    if line.len() < 10 {
+        return Err(Diagnostic::at_end(number, line, "expected 10 characters"));
+    }
+    if let Some((offset, _)) = letters.next() {
+        return Err(Diagnostic::at(number, line, &line[offset..], "unexpected characters after the seat"));
+    }
+    let row = to_integer_from_binary_string(&line[..7], 'B')
+        .map_err(|error| Diagnostic::new(number, line, error))?;
+    let seat = to_integer_from_binary_string(&line[7..], 'R')
+        .map_err(|error| Diagnostic::new(number, line, error))?;
+    Ok(8 * row + seat)
+}
+
+pub struct DayFive;
 
-    println!("Result: {} {}", converted.last().unwrap_or(&0u32), task_two.unwrap_or(0));
+impl Solution for DayFive {
+    type Input<'a> = Vec<u32>;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(
+            numbered_non_blank_lines(lines)
+                .filter_map(|(number, line)| diagnostics.check(parse_seat(number, line)))
+                .sorted()
+                .collect()
+        )
+    }
+
+    fn part_one(&self, seats: &Self::Input<'_>) -> Option<u32> {
+        seats.last().copied()
+    }
+
+    fn part_two(&self, seats: &Self::Input<'_>) -> Option<u32> {
+        seats.windows(2).find(|seats| {
+            seats[0] + 1 != seats[1] && seats[1] - 2 == seats[0]
+        })
+            .map(|result| result[0] + 1)
+    }
 }
 
 #[cfg(test)]
@@ -35,4 +77,12 @@ mod tests {
     fn should_convert_binary_str_to_decimal() {
         assert_eq!(to_integer_from_binary_string("WWbeW", 'W'), Ok(25))
     }
+
+    #[test]
+    fn should_parse_seat_and_point_at_bad_letter() {
+        assert_eq!(parse_seat(1, "FBFBBFFRLR"), Ok(357));
+        assert_eq!(parse_seat(2, "FBFBBFFRXR").map_err(|it| it.column), Err(9));
+        assert_eq!(parse_seat(3, "FBFBBFF").map_err(|it| it.column), Err(8));
+        assert_eq!(parse_seat(4, "FBFBBFFRLRL").map_err(|it| it.column), Err(11))
+    }
 }
@@ -1,55 +1,67 @@
-use crate::file_util::read_lines;
 use std::collections::HashMap;
-use itertools::Itertools;
 use std::str::FromStr;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::file_util::{records, Record};
+use crate::solution::Solution;
 
 const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
 
-struct Credentials {
-    credentials: Vec<Credential>
+pub struct Credentials<'a> {
+    credentials: Vec<Credential<'a>>
 }
 
-impl Credentials {
-    fn new(data: HashMap<String, String>) -> Credentials {
+impl<'a> Credentials<'a> {
+    fn new(data: HashMap<&'a str, &'a str>) -> Credentials<'a> {
         Credentials {
-            credentials: data.iter()
-                .filter_map(|(key, value)| match key.as_str() {
-                    "byr" => Some(Credential::BYR(value.to_owned())),
-                    "iyr" => Some(Credential::IYR(value.to_owned())),
-                    "eyr" => Some(Credential::EYR(value.to_owned())),
-                    "hgt" => Some(Credential::HGT(value.to_owned())),
-                    "hcl" => Some(Credential::HCL(value.to_owned())),
-                    "ecl" => Some(Credential::ECL(value.to_owned())),
-                    "pid" => Some(Credential::PID(value.to_owned())),
+            credentials: data.into_iter()
+                .filter_map(|(key, value)| match key {
+                    "byr" => Some(Credential::BYR(value)),
+                    "iyr" => Some(Credential::IYR(value)),
+                    "eyr" => Some(Credential::EYR(value)),
+                    "hgt" => Some(Credential::HGT(value)),
+                    "hcl" => Some(Credential::HCL(value)),
+                    "ecl" => Some(Credential::ECL(value)),
+                    "pid" => Some(Credential::PID(value)),
                     _ => None
                 })
                 .collect()
         }
     }
 
-    fn is_valid_for_task_one(&self) -> bool {
+    /**
+    * All seven required fields are present.
+    */
+    pub fn is_valid_for_task_one(&self) -> bool {
         self.credentials.len() == 7
     }
 
-    fn is_valid_for_task_two(&self) -> bool {
+    /**
+    * All seven required fields are present and valid.
+    */
+    pub fn is_valid_for_task_two(&self) -> bool {
         self.is_valid_for_task_one() &&
             !self.credentials.iter().any(|c| !c.is_valid())
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq)]
-enum Credential {
-    BYR(String),
-    IYR(String),
-    EYR(String),
-    HGT(String),
-    HCL(String),
-    ECL(String),
-    PID(String)
+pub enum Credential<'a> {
+    BYR(&'a str),
+    IYR(&'a str),
+    EYR(&'a str),
+    HGT(&'a str),
+    HCL(&'a str),
+    ECL(&'a str),
+    PID(&'a str)
 }
 
-impl Credential {
-    fn is_valid(&self) -> bool {
+impl Credential<'_> {
+    /**
+    * Checks the field's value against the passport rules.
+    */
+    pub fn is_valid(&self) -> bool {
         match self {
             Credential::EYR(v) => is_number_between(v, 2020, 2030),
             Credential::IYR(v) => is_number_between(v, 2010, 2020),
@@ -57,14 +69,14 @@ impl Credential {
             Credential::HCL(v) => v.starts_with('#')
                 && v.len() == 7
                 && v.chars().skip(1).find(|c| !c.is_ascii_hexdigit()).is_none(),
-            Credential::HGT(v) => if v.ends_with("cm") {
-                is_number_between(&v[..v.len()-2], 150, 193)
-            } else if v.ends_with("in") {
-                is_number_between(&v[..v.len()-2], 59, 76)
+            Credential::HGT(v) => if let Some(height) = v.strip_suffix("cm") {
+                is_number_between(height, 150, 193)
+            } else if let Some(height) = v.strip_suffix("in") {
+                is_number_between(height, 59, 76)
             } else {
                 false
             },
-            Credential::ECL(v) => EYE_COLORS.contains(&v.as_str()),
+            Credential::ECL(v) => EYE_COLORS.contains(v),
             Credential::PID(v) => v.chars().find(|c| !c.is_numeric()).is_none()
                 && v.len() == 9
         }
@@ -77,48 +89,50 @@ fn is_number_between(value: &str, start: u16, end: u16) -> bool {
         .is_some()
 }
 
-fn convert_to_credentials(iterator: impl Iterator<Item = String>) -> impl Iterator<Item =Credentials> {
-    iterator
-        .batching(|iterator| {
-            let next = iterator
-                .take_while(|value| !value.is_empty())
-                .join(" ");
-            if next.is_empty() {
-                None
-            } else {
-                Some(next)
-            }
-        })
-        .map(|line|
-            Credentials::new(
-                line
-                    .as_str()
-                    .split(' ')
-                    .filter_map(|part| {
-                        let index = part.find(':')?;
-                        Some((String::from(&part[..index]), String::from(&part[index+1..])))
-                    })
-                    .collect()
-            )
-        )
+/**
+* Reads a passport, its `key:value` fields spread over one or more lines. A field without a
+* `:` rejects the whole passport.
+*/
+fn parse_passport<'a>(record: &Record<'a>) -> Result<Credentials<'a>, Diagnostic> {
+    let mut fields = HashMap::new();
+    for (number, line) in record.lines.iter().copied() {
+        for field in line.split_whitespace() {
+            let (key, value) = field.split_once(':')
+                .ok_or_else(|| Diagnostic::at(number, line, field, "expected '<key>:<value>'"))?;
+            fields.insert(key, value);
+        }
+    }
+    Ok(Credentials::new(fields))
 }
 
-#[allow(dead_code)]
-pub fn run_day_four() {
-    let converted = convert_to_credentials(
-        read_lines("assets/day_four")
-    );
-    let mut results = [0, 0];
-    converted.for_each(|credentials| {
-        if credentials.is_valid_for_task_one() {
-            results[0] += 1
-        }
-        if credentials.is_valid_for_task_two() {
-            results[1] += 1
-        }
-    });
+/**
+* Groups the blank line separated passport records into credentials, recording the passports
+* that do not parse.
+*/
+pub fn convert_to_credentials<'a>(iterator: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Vec<Credentials<'a>> {
+    records(iterator)
+        .filter_map(|record| diagnostics.check(parse_passport(&record)))
+        .collect()
+}
+
+pub struct DayFour;
+
+impl Solution for DayFour {
+    type Input<'a> = Vec<Credentials<'a>>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(convert_to_credentials(lines, diagnostics))
+    }
 
-    println!("Result: {} {}", results[0], results[1])
+    fn part_one(&self, credentials: &Self::Input<'_>) -> Option<usize> {
+        Some(credentials.iter().filter(|it| it.is_valid_for_task_one()).count())
+    }
+
+    fn part_two(&self, credentials: &Self::Input<'_>) -> Option<usize> {
+        Some(credentials.iter().filter(|it| it.is_valid_for_task_two()).count())
+    }
 }
 
 #[cfg(test)]
@@ -132,20 +146,17 @@ mod tests {
         iyr:2017 cid:147 hgt:183cm
 
         ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 cid:147 hgt:183cm");
-        let result = convert_to_credentials(
-            under_test.lines().map(|x| x.to_string())
-        )
-            .collect::<Vec<Credentials>>();
+        let result = convert_to_credentials(under_test.lines(), &mut Diagnostics::new());
         let first_result = result.first().unwrap();
 
         assert_eq!(result.len(), 2);
-        assert_eq!(first_result.credentials.contains(&Credential::ECL("gry".into())), true);
-        assert_eq!(first_result.credentials.contains(&Credential::PID("860033327".into())), true);
-        assert_eq!(first_result.credentials.contains(&Credential::EYR("2020".into())), true);
-        assert_eq!(first_result.credentials.contains(&Credential::HCL("#fffffd".into())), true);
-        assert_eq!(first_result.credentials.contains(&Credential::BYR("1937".into())), true);
-        assert_eq!(first_result.credentials.contains(&Credential::IYR("2017".into())), true);
-        assert_eq!(first_result.credentials.contains(&Credential::HGT("183cm".into())), true)
+        assert!(first_result.credentials.contains(&Credential::ECL("gry")));
+        assert!(first_result.credentials.contains(&Credential::PID("860033327")));
+        assert!(first_result.credentials.contains(&Credential::EYR("2020")));
+        assert!(first_result.credentials.contains(&Credential::HCL("#fffffd")));
+        assert!(first_result.credentials.contains(&Credential::BYR("1937")));
+        assert!(first_result.credentials.contains(&Credential::IYR("2017")));
+        assert!(first_result.credentials.contains(&Credential::HGT("183cm")))
     }
 
     #[test]
@@ -163,9 +174,8 @@ mod tests {
 
             hcl:#cfa07d eyr:2025 pid:166559648
             iyr:2011 ecl:brn hgt:59in";
-        let creds = convert_to_credentials(
-            under_test.lines().map(|x| String::from(x.trim())).into_iter()
-        )
+        let creds = convert_to_credentials(under_test.lines().map(str::trim), &mut Diagnostics::new())
+            .iter()
             .filter(|credential| credential.is_valid_for_task_one())
             .count();
         assert_eq!(creds, 2)
@@ -200,11 +210,26 @@ mod tests {
 
         iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719
         ";
-        let creds = convert_to_credentials(
-            under_test.lines().map(|x| String::from(x.trim())).into_iter()
-        )
+        let creds = convert_to_credentials(under_test.lines().map(str::trim), &mut Diagnostics::new())
+            .iter()
             .filter(|credential| credential.is_valid_for_task_two())
             .count();
         assert_eq!(creds, 4)
     }
+
+    #[test]
+    fn should_reject_passports_with_fields_missing_a_colon() {
+        let mut diagnostics = Diagnostics::new();
+        let credentials = convert_to_credentials(
+            vec!("ecl:gry pid:860033327", "eyr2020 hcl:#fffffd", "", "byr:1937").into_iter(),
+            &mut diagnostics
+        );
+        assert_eq!(credentials.len(), 1);
+        assert_eq!(
+            diagnostics.rejected().iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.width))
+                .collect::<Vec<(usize, usize, usize)>>(),
+            vec!((2, 1, 7))
+        )
+    }
 }
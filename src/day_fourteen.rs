@@ -1,29 +1,84 @@
-use crate::file_util::read_non_blank_lines;
+use crate::cancel;
+use crate::file_util::numbered_non_blank_lines;
 use std::str::FromStr;
 use std::collections::HashMap;
 use itertools::Itertools;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::{Part, Solution, BOTH_PARTS};
 
 #[derive(Debug)]
-struct Instructions {
-    mask: String,
-    assignments: Vec<(usize, usize)>
+pub struct Instructions {
+    pub mask: String,
+    pub assignments: Vec<(usize, usize)>
 }
 
-#[allow(dead_code)]
-pub fn run_day_fourteen() {
-    let mut lines = read_non_blank_lines("assets/day_fourteen");
-    let parsed = parse_lines(&mut lines);
-    let sum_part_one: usize = execute_task_one(&parsed)
-        .values().sum();
-    let sum_part_two: usize = execute_task_two(&parsed)
-        .values().sum();
-    println!("Part 1 {}", sum_part_one);
-    println!("Part 2 {}", sum_part_two);
+pub struct DayFourteen;
+
+impl Solution for DayFourteen {
+    type Input<'a> = Vec<Instructions>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(parse_lines(lines, diagnostics))
+    }
+
+    fn part_one(&self, instructions: &Self::Input<'_>) -> Option<usize> {
+        Some(execute_task_one(instructions).values().sum())
+    }
+
+    fn part_two(&self, instructions: &Self::Input<'_>) -> Option<usize> {
+        Some(execute_task_two(instructions)?.values().sum())
+    }
+
+    fn reference_parts(&self) -> &'static [Part] {
+        BOTH_PARTS
+    }
+
+    fn reference_part_one(&self, instructions: &Self::Input<'_>) -> Option<usize> {
+        let mut memory = HashMap::new();
+        for instruction in instructions.iter() {
+            for (address, value) in instruction.assignments.iter() {
+                let masked = mask_bits(&instruction.mask).fold(*value, |value, (bit, mask)| match mask {
+                    '0' => value & !bit,
+                    '1' => value | bit,
+                    _ => value
+                });
+                memory.insert(*address, masked);
+            }
+        }
+        Some(memory.values().sum())
+    }
+
+    fn reference_part_two(&self, instructions: &Self::Input<'_>) -> Option<usize> {
+        let mut memory = HashMap::new();
+        for instruction in instructions.iter() {
+            for (address, value) in instruction.assignments.iter() {
+                let addresses = mask_bits(&instruction.mask).fold(vec!(*address), |addresses, (bit, mask)| match mask {
+                    '1' => addresses.into_iter().map(|address| address | bit).collect(),
+                    'X' => addresses.into_iter().flat_map(|address| vec!(address | bit, address & !bit)).collect(),
+                    _ => addresses
+                });
+                for address in addresses {
+                    memory.insert(address, *value);
+                }
+            }
+        }
+        Some(memory.values().sum())
+    }
 }
 
-fn execute_task_two(instructions: &[Instructions]) -> HashMap<usize, usize> {
+/**
+* Runs the program with the mask applied to addresses, returning memory, or `None` if
+* cancelled.
+*/
+pub fn execute_task_two(instructions: &[Instructions]) -> Option<HashMap<usize, usize>> {
     let mut address_space = HashMap::new();
     for instruction in instructions.iter() {
+        if cancel::requested() {
+            return None;
+        }
         let ones_mask = get_ones_mask(&instruction.mask);
         let x_masks: Vec<(usize,usize)> = instruction.mask.chars()
             .rev()
@@ -51,7 +106,14 @@ fn execute_task_two(instructions: &[Instructions]) -> HashMap<usize, usize> {
             }
         }
     }
-    address_space
+    Some(address_space)
+}
+
+/**
+* Each character of the mask with the bit it applies to, lowest bit first.
+*/
+fn mask_bits(mask: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    mask.chars().rev().enumerate().map(|(index, mask)| (1 << index, mask))
 }
 
 fn get_ones_mask(mask: &str) -> Option<usize> {
@@ -64,7 +126,10 @@ fn get_ones_mask(mask: &str) -> Option<usize> {
     ).ok()
 }
 
-fn execute_task_one(instructions: &[Instructions]) -> HashMap<usize, usize> {
+/**
+* Runs the program with the mask applied to values, returning memory.
+*/
+pub fn execute_task_one(instructions: &[Instructions]) -> HashMap<usize, usize> {
     let mut address_space = HashMap::new();
     for instruction in instructions.iter() {
         for assignment in instruction.assignments.iter() {
@@ -87,33 +152,50 @@ fn execute_task_one(instructions: &[Instructions]) -> HashMap<usize, usize> {
     address_space
 }
 
-fn parse_lines(lines: &mut impl Iterator<Item = String>) -> Vec<Instructions> {
-    let next_mask = lines.next();
-    let mut result = Vec::new();
-    if let Some(mut mask) = next_mask {
-        let mut assignments = Vec::new();
-        for assignment in lines {
-            let mut split_assignment = assignment.splitn(2, " = ");
-            if let Some(split) = split_assignment.next().zip(split_assignment.next()) {
-                if split.0 == "mask" {
-                    let current_assignments = assignments;
-                    assignments = Vec::new();
-                    result.push(
-                        Instructions {
-                            mask: String::from(&mask[7..]), assignments: current_assignments
-                        }
-                    );
-                    mask = assignment;
-                } else {
-                    let address = usize::from_str(&split.0[4..split.0.len()-1]);
-                    let value = usize::from_str(split.1);
-                    if let Some(assignment) = address.ok().zip(value.ok()) {
-                        assignments.push(assignment);
-                    }
-                }
-            }
+/**
+* A line of the program: a new mask, or a value written to an address.
+*/
+enum Line<'a> {
+    Mask(&'a str),
+    Assignment(usize, usize)
+}
+
+/**
+* Parses a line such as `mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X` or `mem[8] = 11`.
+*/
+fn parse_line(number: usize, line: &str) -> Result<Line<'_>, Diagnostic> {
+    let (target, value) = line.split_once(" = ")
+        .ok_or_else(|| Diagnostic::new(number, line, "expected 'mask = <mask>' or 'mem[<address>] = <value>'"))?;
+    if target == "mask" {
+        if value.len() != 36 || !value.chars().all(|bit| matches!(bit, '0' | '1' | 'X')) {
+            return Err(Diagnostic::at(number, line, value, "expected 36 of '0', '1' or 'X'"));
+        }
+        return Ok(Line::Mask(value));
+    }
+    let address = target.strip_prefix("mem[")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| Diagnostic::at(number, line, target, "expected 'mask' or 'mem[<address>]'"))?;
+    Ok(Line::Assignment(
+        usize::from_str(address).map_err(|error| Diagnostic::at(number, line, address, error))?,
+        usize::from_str(value).map_err(|error| Diagnostic::at(number, line, value, error))?
+    ))
+}
+
+/**
+* Groups the program into masks and the memory assignments that follow them, recording the
+* lines that do not parse. Assignments before the first mask are rejected too.
+*/
+pub fn parse_lines<'a>(lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Vec<Instructions> {
+    let mut result: Vec<Instructions> = Vec::new();
+    for (number, line) in numbered_non_blank_lines(lines) {
+        match diagnostics.check(parse_line(number, line)) {
+            Some(Line::Mask(mask)) => result.push(Instructions { mask: String::from(mask), assignments: Vec::new() }),
+            Some(Line::Assignment(address, value)) => match result.last_mut() {
+                Some(instructions) => instructions.assignments.push((address, value)),
+                None => diagnostics.reject(Diagnostic::new(number, line, "expected a mask before the first assignment"))
+            },
+            None => {}
         }
-        result.push(Instructions { mask, assignments })
     }
     result
 }
@@ -126,7 +208,7 @@ mod tests {
     #[test]
     fn should_resolve_part_2() {
         let result = execute_task_two(
-            &vec!(
+            &[
                 Instructions {
                     mask: String::from("000000000000000000000000000000X1001X"),
                     assignments: vec!((42, 100))
@@ -135,11 +217,36 @@ mod tests {
                     mask: String::from("00000000000000000000000000000000X0XX"),
                     assignments: vec!((26, 1))
                 }
-            )
-        );
+            ]
+        ).unwrap();
         assert_eq!(
             result.values().sum::<usize>(),
             208
         )
     }
+
+    #[test]
+    fn should_point_at_malformed_lines() {
+        let mut diagnostics = Diagnostics::new();
+        let instructions = parse_lines(
+            vec!(
+                "mem[1] = 2",
+                "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X",
+                "ab = 5",
+                "mem[x] = 5",
+                "mem[8] = 11",
+                "mem[9] 3",
+                "mask = 12"
+            ).into_iter(),
+            &mut diagnostics
+        );
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].assignments, vec!((8, 11)));
+        assert_eq!(
+            diagnostics.rejected().iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.width))
+                .collect::<Vec<(usize, usize, usize)>>(),
+            vec!((1, 1, 10), (3, 1, 2), (4, 5, 1), (6, 1, 8), (7, 8, 2))
+        )
+    }
 }
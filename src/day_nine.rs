@@ -1,30 +1,60 @@
-use crate::file_util::read_non_blank_lines;
-use std::str::FromStr;
+use crate::cancel;
+use crate::file_util::parse_lines_skipping;
 use std::cmp::{min, max};
+use crate::diagnostics::Diagnostics;
+use crate::error::Error;
+use crate::solution::{Part, Solution, BOTH_PARTS};
 
-#[allow(dead_code)]
-pub fn run_day_nine() {
-    let lines = read_non_blank_lines("assets/day_nine")
-        .filter_map(|line| usize::from_str(line.as_str()).ok())
-        .collect::<Vec<usize>>();
-
-    let calculated_result = solve_part_one(&lines);
-
-    if let Some(result) = calculated_result {
-        println!("Result P1: {}", result);
-        let result2 = solve_part_two(result, &lines);
-        if let Some((smallest, largest)) = result2 {
-            println!(
-               "Result P2: Smallest {} and Largest {} sum to {}",
-               smallest,
-               largest,
-               smallest + largest
-            )
-        }
+pub struct DayNine;
+
+impl Solution for DayNine {
+    type Input<'a> = Vec<usize>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(parse_lines_skipping(lines, diagnostics))
+    }
+
+    fn part_one(&self, numbers: &Self::Input<'_>) -> Option<usize> {
+        solve_part_one(numbers)
+    }
+
+    fn part_two(&self, numbers: &Self::Input<'_>) -> Option<usize> {
+        solve_part_two(solve_part_one(numbers)?, numbers)
+            .map(|(smallest, largest)| smallest + largest)
+    }
+
+    fn reference_parts(&self) -> &'static [Part] {
+        BOTH_PARTS
+    }
+
+    fn reference_part_one(&self, numbers: &Self::Input<'_>) -> Option<usize> {
+        numbers.iter()
+            .enumerate()
+            .skip(25)
+            .find(|(index, number)| {
+                let window = &numbers[index - 25..*index];
+                !window.iter().enumerate().any(|(position, first)| {
+                    window[position + 1..].iter().any(|second| first + second == **number)
+                })
+            })
+            .map(|(_, number)| *number)
+    }
+
+    fn reference_part_two(&self, numbers: &Self::Input<'_>) -> Option<usize> {
+        let target = self.reference_part_one(numbers)?;
+        (0..numbers.len())
+            .flat_map(|start| (start + 2..=numbers.len()).map(move |end| &numbers[start..end]))
+            .find(|run| run.iter().sum::<usize>() == target)
+            .map(|run| run.iter().min().unwrap_or(&0) + run.iter().max().unwrap_or(&0))
     }
 }
 
-fn solve_part_one(data: &[usize]) -> Option<usize> {
+/**
+* Finds the first number that is not the sum of two of the 25 before it.
+*/
+pub fn solve_part_one(data: &[usize]) -> Option<usize> {
     let mut history = [0; 25];
     let mut manipulated_history = [0; 25];
     let mut iter = data.iter();
@@ -55,7 +85,10 @@ fn read_into_buffers<'a>(
     })
 }
 
-fn is_number_sum_of_any(value: usize, numbers: &mut[usize]) -> bool {
+/**
+* Whether two of `numbers` add up to `value`. Sorts `numbers`.
+*/
+pub fn is_number_sum_of_any(value: usize, numbers: &mut[usize]) -> bool {
     numbers.sort_unstable();
     for x in 0..numbers.len() {
         let current_value = numbers[x];
@@ -68,11 +101,18 @@ fn is_number_sum_of_any(value: usize, numbers: &mut[usize]) -> bool {
     false
 }
 
-fn solve_part_two(number: usize, numbers: &[usize]) -> Option<(usize, usize)> {
+/**
+* Finds a contiguous run adding up to `number`, returning its smallest and
+* largest values. Gives up if cancelled.
+*/
+pub fn solve_part_two(number: usize, numbers: &[usize]) -> Option<(usize, usize)> {
     let mut sum;
     let mut smallest;
     let mut largest;
     for i in 0..numbers.len() {
+        if cancel::requested() {
+            return None;
+        }
         sum = numbers[i];
         smallest = sum;
         largest = sum;
@@ -100,7 +140,7 @@ mod tests {
         assert_eq!(
             solve_part_two(
                 17,
-                &vec!(1, 32, 4, 7, 6, 78)
+                &[1, 32, 4, 7, 6, 78]
             ),
             Some((4, 7))
         )
@@ -108,28 +148,25 @@ mod tests {
 
     #[test]
     fn should_determine_if_number_is_sum_of_others() {
-        assert_eq!(
+        assert!(
             is_number_sum_of_any(
                 25,
                 &mut [1, 24]
-            ),
-            true
+            )
         );
 
-        assert_eq!(
-            is_number_sum_of_any(
+        assert!(
+            !is_number_sum_of_any(
                 25,
                 &mut [1, 23]
-            ),
-            false
+            )
         );
 
-        assert_eq!(
+        assert!(
             is_number_sum_of_any(
                 25,
                 &mut [1, 7, 8, 22, 3, 13, 24]
-            ),
-            true
+            )
         );
     }
 }
@@ -1,10 +1,14 @@
-use crate::file_util::read_lines;
-use std::collections::{HashMap};
+use std::collections::{HashMap, HashSet};
 use crate::day_nineteen::ProductionRule::*;
 use regex::Regex;
+use crate::cancel;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::file_util::records;
+use crate::error::Error;
+use crate::solution::{Part, Solution};
 
 #[derive(PartialEq, Eq, Debug)]
-enum ProductionRule {
+pub enum ProductionRule {
     TerminalSymbol(char),
     Reference(usize),
     And(Box<ProductionRule>, Box<ProductionRule>),
@@ -12,101 +16,146 @@ enum ProductionRule {
 }
 
 impl ProductionRule {
-    fn expand<'a>(&self, buffer: &mut String, lookup: &impl Fn(usize) -> &'a ProductionRule) {
+    /**
+    * Appends the regular expression for this rule to `buffer`, or returns `None` if it refers to
+    * a rule that `lookup` does not know, refers back to one of the rules in `visiting` that it is
+    * being expanded for, or the expansion is cancelled.
+    */
+    fn expand<'a>(
+        &self,
+        buffer: &mut String,
+        lookup: &impl Fn(usize) -> Option<&'a ProductionRule>,
+        visiting: &mut HashSet<usize>
+    ) -> Option<()> {
+        if cancel::requested() {
+            return None;
+        }
         match self {
             TerminalSymbol(x) => {
                 buffer.push(*x)
             },
-            Reference(x) => lookup(*x).expand(buffer, lookup),
+            Reference(x) => {
+                if !visiting.insert(*x) {
+                    return None;
+                }
+                lookup(*x)?.expand(buffer, lookup, visiting)?;
+                visiting.remove(x);
+            },
             And(x, y) => {
-                x.expand(buffer, lookup);
-                y.expand(buffer, lookup)
+                x.expand(buffer, lookup, visiting)?;
+                y.expand(buffer, lookup, visiting)?
             },
             Or(x, y) => {
                 buffer.push('(');
-                x.expand(buffer, lookup);
+                x.expand(buffer, lookup, visiting)?;
                 buffer.push('|');
-                y.expand(buffer, lookup);
+                y.expand(buffer, lookup, visiting)?;
                 buffer.push(')');
             }
         }
+        Some(())
     }
 }
 
-#[allow(dead_code)]
-pub fn run_day_nineteen() {
-    let mut input_iter = read_lines("assets/day_nineteen");
-    let grammar_rules = parse_grammar_into_rules(
-        &mut input_iter.by_ref().take_while(|it| !it.trim().is_empty())
-    );
-    let regex = Regex::new(
-        &format!("^{}$", convert_grammar_rules_to_regex(&grammar_rules).unwrap())
-    )
-        .unwrap();
-    let matching_sentences = input_iter
-        .filter(|x| !x.trim().is_empty())
-        .filter(|x| regex.is_match(&x))
-        .count();
-    println!("Result Task 1 {:?}", matching_sentences);
+pub struct DayNineteen;
+
+impl Solution for DayNineteen {
+    type Input<'a> = (HashMap<usize, ProductionRule>, Vec<&'a str>);
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        let mut groups = records(lines);
+        let grammar_rules = parse_grammar_into_rules(
+            &mut groups.next().map(|grammar| grammar.lines).unwrap_or_default().into_iter(),
+            diagnostics
+        );
+        let sentences = groups
+            .flat_map(|group| group.lines)
+            .map(|(_, x)| x)
+            .collect();
+        Ok((grammar_rules, sentences))
+    }
+
+    fn part_one(&self, (grammar_rules, sentences): &Self::Input<'_>) -> Option<usize> {
+        let regex = Regex::new(
+            &format!("^{}$", convert_grammar_rules_to_regex(grammar_rules)?)
+        ).ok()?;
+        Some(sentences.iter().filter(|x| regex.is_match(x)).count())
+    }
+
+    fn part_two(&self, _: &Self::Input<'_>) -> Option<usize> {
+        None
+    }
+
+    fn parts(&self) -> &'static [Part] {
+        &[Part::One]
+    }
 }
 
-fn convert_grammar_rules_to_regex(rules: &HashMap<usize, ProductionRule>) -> Option<String> {
+/**
+* Expands rule zero into a regular expression, without anchors. Gives `None` if rule zero, or
+* any rule it refers to, is missing, if the rules loop, as a regular expression cannot match
+* them, or if cancelled.
+*/
+pub fn convert_grammar_rules_to_regex(rules: &HashMap<usize, ProductionRule>) -> Option<String> {
     let start = rules.get(&0_usize)?;
     let mut result = String::new();
-    start.expand(&mut result, &|x| rules.get(&x).unwrap());
+    start.expand(&mut result, &|x| rules.get(&x), &mut HashSet::from([0]))?;
     Some(result)
 }
 
-fn parse_grammar_into_rules(iter: &mut impl Iterator<Item = String>) -> HashMap<usize, ProductionRule>{
-    let mut rules = HashMap::new();
-    for line in iter {
-        let mut split = line.splitn(2, ':');
-        if let Some((rule, rest)) = split.next()
-            .and_then(|it| it.parse::<usize>().ok())
-            .zip(split.next()) {
-            if rest.contains('"') {
-                let terminal = rest.chars()
-                    .skip_while(|it| *it != '"')
-                    .nth(1);
-                if let Some(character) = terminal {
-                    rules.insert(rule, TerminalSymbol(character));
-                }
-            } else {
-                let mut split = rest.split('|')
-                    .filter_map(|sub_rule| {
-                        let productions = sub_rule.split(' ')
-                            .filter_map(|it| it.trim().parse::<usize>().ok())
-                            .collect::<Vec<usize>>();
-                        if !productions.is_empty() {
-                            Some(
-                                productions.iter().skip(1)
-                                    .fold(
-                                        Reference(productions[0]),
-                                        |prev, production| {
-                                            And(
-                                                Box::new(prev),
-                                                Box::new(Reference(*production))
-                                            )
-                                        }
-                                    )
-                            )
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<ProductionRule>>();
-                if let Some(last) = split.pop() {
-                    rules.insert(
-                        rule,
-                        split.into_iter().rev().fold(last, |prev, next| {
-                            Or(Box::new(next), Box::new(prev))
-                        })
-                    );
-                }
-            }
-        }
+/**
+* Reads every rule, recording the lines that do not parse.
+*/
+pub fn parse_grammar_into_rules<'a>(
+    iter: &mut impl Iterator<Item = (usize, &'a str)>,
+    diagnostics: &mut Diagnostics
+) -> HashMap<usize, ProductionRule> {
+    iter.filter_map(|(number, line)| diagnostics.check(parse_grammar_rule(number, line)))
+        .collect()
+}
+
+/**
+* Parses a rule such as `1: 2 3 | 3 2` or `4: "a"`.
+*/
+pub fn parse_grammar_rule(number: usize, line: &str) -> Result<(usize, ProductionRule), Diagnostic> {
+    let (rule, rest) = line.split_once(':')
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected ':' after the rule number"))?;
+    let rule = rule.parse::<usize>()
+        .map_err(|error| Diagnostic::at(number, line, rule, error))?;
+    if rest.contains('"') {
+        let quoted = rest.trim();
+        let mut terminal = quoted.chars();
+        return match (terminal.next(), terminal.next(), terminal.next(), terminal.next()) {
+            (Some('"'), Some(character), Some('"'), None) => Ok((rule, TerminalSymbol(character))),
+            _ => Err(Diagnostic::at(number, line, quoted, "expected a single quoted character"))
+        };
     }
-    rules
+    rest.split('|')
+        .map(|sub_rule| parse_sequence(number, line, sub_rule))
+        .collect::<Result<Vec<ProductionRule>, Diagnostic>>()?
+        .into_iter()
+        .rev()
+        .reduce(|prev, next| Or(Box::new(next), Box::new(prev)))
+        .map(|production| (rule, production))
+        .ok_or_else(|| Diagnostic::at(number, line, rest, "expected at least one production"))
+}
+
+fn parse_sequence(number: usize, line: &str, sub_rule: &str) -> Result<ProductionRule, Diagnostic> {
+    let productions = sub_rule.split_whitespace()
+        .map(|it| it.parse::<usize>().map_err(|error| Diagnostic::at(number, line, it, error)))
+        .collect::<Result<Vec<usize>, Diagnostic>>()?;
+    let (first, rest) = productions.split_first()
+        .ok_or_else(|| Diagnostic::at(number, line, sub_rule, "expected at least one rule number"))?;
+    Ok(
+        rest.iter().fold(Reference(*first), |prev, production| {
+            And(
+                Box::new(prev),
+                Box::new(Reference(*production))
+            )
+        })
+    )
 }
 
 #[cfg(test)]
@@ -115,31 +164,62 @@ mod tests {
 
     #[test]
     fn should_recognise_valid_strings() {
-        let mut to_process = vec!(
-            String::from("0: 4 1 5"),
-            String::from("1: 2 3 | 3 2"),
-            String::from("2: 4 4 | 5 5"),
-            String::from("3: 4 5 | 5 4"),
-            String::from("4: \"a\""),
-            String::from("5: \"b\""),
-            String::from(""),
-            String::from("ababbb"),
-            String::from("bababa"),
-            String::from("abbbab"),
-            String::from("aaabbb"),
-            String::from("aaaabbb")
-        ).into_iter();
+        let mut to_process = records(vec!(
+            "0: 4 1 5",
+            "1: 2 3 | 3 2",
+            "2: 4 4 | 5 5",
+            "3: 4 5 | 5 4",
+            "4: \"a\"",
+            "5: \"b\"",
+            "",
+            "ababbb",
+            "bababa",
+            "abbbab",
+            "aaabbb",
+            "aaaabbb"
+        ).into_iter());
 
+        let mut diagnostics = Diagnostics::new();
         let grammar_rules = parse_grammar_into_rules(
-            &mut to_process.by_ref().take_while(|it| !it.trim().is_empty())
+            &mut to_process.next().unwrap().lines.into_iter(),
+            &mut diagnostics
         );
+        assert!(diagnostics.rejected().is_empty());
         let regex = Regex::new(
             &format!("^{}$", convert_grammar_rules_to_regex(&grammar_rules).unwrap())
         ).unwrap();
         let matching_sentences = to_process
-            .filter(|x| !x.trim().is_empty())
-            .filter(|x| regex.is_match(&x))
+            .flat_map(|group| group.lines)
+            .filter(|(_, x)| regex.is_match(x))
             .count();
         assert_eq!(matching_sentences, 2);
     }
+
+    #[test]
+    fn should_point_at_bad_production() {
+        let diagnostic = parse_grammar_rule(3, "3: 4 x | 5 4").unwrap_err();
+        assert_eq!((diagnostic.column, diagnostic.width), (6, 1));
+        let diagnostic = parse_grammar_rule(4, "4: \"ab\"").unwrap_err();
+        assert_eq!((diagnostic.column, diagnostic.width), (4, 4))
+    }
+
+    #[test]
+    fn should_not_expand_missing_rules() {
+        let mut diagnostics = Diagnostics::new();
+        let grammar_rules = parse_grammar_into_rules(
+            &mut vec!((1, "0: 1 2"), (2, "1: \"a\"")).into_iter(),
+            &mut diagnostics
+        );
+        assert_eq!(convert_grammar_rules_to_regex(&grammar_rules), None)
+    }
+
+    #[test]
+    fn should_not_expand_rules_that_loop() {
+        let mut diagnostics = Diagnostics::new();
+        let grammar_rules = parse_grammar_into_rules(
+            &mut vec!((1, "0: 1"), (2, "1: 0")).into_iter(),
+            &mut diagnostics
+        );
+        assert_eq!(convert_grammar_rules_to_regex(&grammar_rules), None)
+    }
 }
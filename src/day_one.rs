@@ -1,28 +1,347 @@
+use std::any::type_name;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+use std::num::ParseIntError;
+use std::rc::Rc;
+use std::str::FromStr;
 use itertools::Itertools;
-use crate::file_util::read_lines_as_u32;
+use crate::cancel;
+use crate::file_util::parse_lines_skipping;
+use crate::diagnostics::Diagnostics;
+use crate::error::Error;
+use crate::solution::{Part, Solution, BOTH_PARTS};
 
-fn find_pair_summing_to(numbers: &[u32], value: u32) -> Option<(&u32, &u32)> {
-    numbers.iter().tuple_combinations()
-        .find(|(first, second)| *first + *second == value)
+/**
+* An integer type an expense report can hold. Sums are worked out in `i128`, so every amount
+* fits in one; sums that leave `i128` part way through are not found.
+*/
+pub trait Amount: Copy + Ord + fmt::Display + fmt::Debug + Into<i128> + TryFrom<i128> + FromStr<Err = ParseIntError> + Send + Sync + 'static {
+    const ONE: Self;
+
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! amount {
+    ($($type:ty),*) => {
+        $(
+            impl Amount for $type {
+                const ONE: Self = 1;
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$type>::checked_mul(self, other)
+                }
+            }
+        )*
+    }
+}
+
+amount!(i8, i16, i32, i64, i128, u8, u16, u32, u64);
+
+/**
+* The product of the entries that were found, or the entries themselves when their product does
+* not fit in the amount type.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub enum Product<T> {
+    Value(T),
+    Overflow(Vec<T>)
+}
+
+pub fn checked_product<T: Amount>(entries: &[&T]) -> Product<T> {
+    entries.iter()
+        .try_fold(T::ONE, |product, entry| product.checked_mul(**entry))
+        .map(Product::Value)
+        .unwrap_or_else(|| Product::Overflow(entries.iter().map(|entry| **entry).collect()))
+}
+
+impl<T: Amount> fmt::Display for Product<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Product::Value(value) => write!(f, "{}", value),
+            Product::Overflow(entries) => write!(
+                f,
+                "overflow: {} does not fit in {}",
+                entries.iter().join(" * "),
+                type_name::<T>()
+            )
+        }
+    }
+}
+
+/**
+* Looks for `k` of the sorted `values[start..]` that add up to `target`, pushing their positions
+* onto `chosen`. The last two are found by closing in from both ends. Finds nothing once
+* cancelled.
+*/
+fn search(values: &[i128], start: usize, k: usize, target: i128, chosen: &mut Vec<usize>) -> bool {
+    match k {
+        0 => target == 0,
+        1 => match values[start..].binary_search(&target) {
+            Ok(found) => {
+                chosen.push(start + found);
+                true
+            },
+            Err(_) => false
+        },
+        2 => {
+            if values.len() < start + 2 {
+                return false;
+            }
+            let (mut low, mut high) = (start, values.len() - 1);
+            while low < high {
+                let sum = values[low].checked_add(values[high]);
+                match sum.map(|sum| sum.cmp(&target)).unwrap_or(values[low].cmp(&0)) {
+                    Ordering::Less => low += 1,
+                    Ordering::Greater => high -= 1,
+                    Ordering::Equal => {
+                        chosen.extend([low, high]);
+                        return true;
+                    }
+                }
+            }
+            false
+        },
+        _ => {
+            for first in start..values.len() {
+                if cancel::requested() || values[first].saturating_mul(k as i128) > target {
+                    break;
+                }
+                if first > start && values[first] == values[first - 1] {
+                    continue;
+                }
+                let rest = match target.checked_sub(values[first]) {
+                    Some(rest) => rest,
+                    None => continue
+                };
+                chosen.push(first);
+                if search(values, first + 1, k - 1, rest, chosen) {
+                    return true;
+                }
+                chosen.pop();
+            }
+            false
+        }
+    }
+}
+
+/**
+* Finds `k` entries that add up to `target`, each entry used at most once, returning them in
+* the order they appear. Searching a sorted copy takes O(n log n) time for a pair and
+* O(n^(k-1)) beyond that.
+*/
+pub fn find_k_summing_to<T: Amount>(numbers: &[T], k: usize, target: T) -> Option<Vec<&T>> {
+    let mut order = (0..numbers.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|index| numbers[*index]);
+    let values = order.iter().map(|index| numbers[*index].into()).collect::<Vec<i128>>();
+    let mut chosen = Vec::with_capacity(k);
+    if !search(&values, 0, k, target.into(), &mut chosen) {
+        return None;
+    }
+    let mut indices = chosen.into_iter().map(|position| order[position]).collect::<Vec<usize>>();
+    indices.sort_unstable();
+    Some(indices.into_iter().map(|index| &numbers[index]).collect())
+}
+
+/**
+* Finds two entries that add up to `value`.
+*/
+pub fn find_pair_summing_to<T: Amount>(numbers: &[T], value: T) -> Option<(&T, &T)> {
+    find_k_summing_to(numbers, 2, value).map(|found| (found[0], found[1]))
+}
+
+/**
+* Finds three entries that add up to `value`.
+*/
+pub fn find_triple_summing_to<T: Amount>(numbers: &[T], value: T) -> Option<(&T, &T, &T)> {
+    find_k_summing_to(numbers, 3, value).map(|found| (found[0], found[1], found[2]))
+}
+
+/**
+* The product of `count` entries that add up to `target`, if there are any.
+*/
+pub fn reconcile<T: Amount>(numbers: &[T], count: usize, target: T) -> Option<Product<T>> {
+    find_k_summing_to(numbers, count, target).map(|found| checked_product(&found))
+}
+
+/**
+* The distinct values in ascending order, each with the indices of the entries holding it.
+*/
+type Groups<T> = Rc<[(T, Vec<usize>)]>;
+
+fn group_values<T: Amount>(numbers: &[T]) -> Groups<T> {
+    let mut order = (0..numbers.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|index| numbers[*index]);
+    let mut groups: Vec<(T, Vec<usize>)> = Vec::new();
+    for index in order {
+        match groups.last_mut() {
+            Some((value, indices)) if *value == numbers[index] => indices.push(index),
+            _ => groups.push((numbers[index], vec!(index)))
+        }
+    }
+    Rc::from(groups)
+}
+
+/**
+* Every way of taking `k` entries from `groups[start..]` that add up to `target`, as how many
+* copies of each group's value are taken. Each multiset of values comes out once. Stops once
+* cancelled.
+*/
+fn group_sums<T: Amount>(groups: Groups<T>, start: usize, k: usize, target: i128) -> Box<dyn Iterator<Item = Vec<(usize, usize)>>> {
+    match k {
+        0 => Box::new((target == 0).then(Vec::new).into_iter()),
+        1 => {
+            let found = groups[start..].binary_search_by_key(&target, |(value, _)| (*value).into());
+            Box::new(found.ok().map(|found| vec!((start + found, 1))).into_iter())
+        },
+        _ => {
+            let smallest = Rc::clone(&groups);
+            Box::new(
+                (start..groups.len())
+                    .take_while(move |group| !cancel::requested() && smallest[*group].0.into().saturating_mul(k as i128) <= target)
+                    .flat_map(move |group| {
+                        let value: i128 = groups[group].0.into();
+                        let groups = Rc::clone(&groups);
+                        (1..=k.min(groups[group].1.len()))
+                            .filter_map(move |copies| Some((copies, target.checked_sub(value.checked_mul(copies as i128)?)?)))
+                            .flat_map(move |(copies, rest)| {
+                                group_sums(Rc::clone(&groups), group + 1, k - copies, rest)
+                                    .map(move |mut taken| {
+                                        taken.insert(0, (group, copies));
+                                        taken
+                                    })
+                            })
+                    })
+            )
+        }
+    }
+}
+
+/**
+* Every distinct multiset of `k` values from the entries that adds up to `target`, in ascending
+* order. A value can appear as many times as entries hold it.
+*/
+pub fn k_sum_values<T: Amount>(numbers: &[T], k: usize, target: T) -> impl Iterator<Item = Vec<T>> {
+    let groups = group_values(numbers);
+    group_sums(Rc::clone(&groups), 0, k, target.into())
+        .map(move |taken| {
+            taken.into_iter()
+                .flat_map(|(group, copies)| std::iter::repeat_n(groups[group].0, copies))
+                .collect()
+        })
+}
+
+/**
+* Every set of `k` distinct entries that adds up to `target`, as their indices in ascending
+* order. Entries with the same value give separate sets, so this can be used to find inputs with
+* more than one answer.
+*/
+pub fn k_sum_indices<T: Amount>(numbers: &[T], k: usize, target: T) -> impl Iterator<Item = Vec<usize>> {
+    let groups = group_values(numbers);
+    group_sums(Rc::clone(&groups), 0, k, target.into())
+        .flat_map(move |taken| -> Box<dyn Iterator<Item = Vec<usize>>> {
+            if taken.is_empty() {
+                return Box::new(std::iter::once(Vec::new()));
+            }
+            let choices = taken.into_iter()
+                .map(|(group, copies)| groups[group].1.iter().copied().combinations(copies).collect::<Vec<Vec<usize>>>())
+                .collect::<Vec<Vec<Vec<usize>>>>();
+            Box::new(
+                choices.into_iter()
+                    .multi_cartesian_product()
+                    .map(|chosen| chosen.into_iter().flatten().sorted().collect())
+            )
+        })
+}
+
+/**
+* The expense report, holding amounts of type `T`. The registered day uses `i64`, so entries
+* can be negative or above the range of `u32`; a product that does not fit is reported as an
+* overflow rather than wrapping.
+*/
+pub struct ExpenseReport<T> {
+    target: i128,
+    count: Option<usize>,
+    amount: PhantomData<T>
+}
+
+impl<T> ExpenseReport<T> {
+    /**
+    * The puzzle: a pair of entries adding up to 2020, then a triple.
+    */
+    pub const fn new() -> Self {
+        ExpenseReport::reconciling(TARGET, None)
+    }
+
+    /**
+    * Looks for entries adding up to `target` instead, which has no answer if it does not fit in
+    * `T`. With a `count`, only part one is answered, from that many entries.
+    */
+    pub const fn reconciling(target: i128, count: Option<usize>) -> Self {
+        ExpenseReport { target, count, amount: PhantomData }
+    }
+
+    fn answer(&self, numbers: &[T], count: usize) -> Option<Product<T>> where T: Amount {
+        reconcile(numbers, count, T::try_from(self.target).ok()?)
+    }
+
+    /**
+    * The first `count` entries in index order that add up to the target, by trying every
+    * combination.
+    */
+    fn answer_slowly(&self, numbers: &[T], count: usize) -> Option<Product<T>> where T: Amount {
+        numbers.iter()
+            .combinations(count)
+            .find(|entries| entries.iter().try_fold(0_i128, |sum, entry| sum.checked_add((**entry).into())) == Some(self.target))
+            .map(|entries| checked_product(&entries))
+    }
 }
 
-fn find_triple_summing_to(numbers: &[u32], value: u32) -> Option<(&u32, &u32, &u32)> {
-    numbers.iter().tuple_combinations()
-        .find(|(first, second, third)| *first + *second + *third == value)
+impl<T> Default for ExpenseReport<T> {
+    fn default() -> Self {
+        ExpenseReport::new()
+    }
 }
 
-#[allow(dead_code)]
-pub fn run_day_one() {
-    let numbers = read_lines_as_u32("assets/day_one").collect_vec();
-    let first_result = find_pair_summing_to(&numbers, 2020);
-    let second_result = find_triple_summing_to(&numbers, 2020);
-    match first_result {
-        None => println!("No match!"),
-        Some(products) => println!("Result: {}", products.0 * products.1)
+pub type DayOne = ExpenseReport<i64>;
+
+pub const TARGET: i128 = 2020;
+
+impl<T: Amount> Solution for ExpenseReport<T> {
+    type Input<'a> = Vec<T>;
+    type PartOne = Product<T>;
+    type PartTwo = Product<T>;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(parse_lines_skipping(lines, diagnostics))
+    }
+
+    fn part_one(&self, numbers: &Self::Input<'_>) -> Option<Product<T>> {
+        self.answer(numbers, self.count.unwrap_or(2))
+    }
+
+    fn part_two(&self, numbers: &Self::Input<'_>) -> Option<Product<T>> {
+        self.answer(numbers, 3)
+    }
+
+    fn parts(&self) -> &'static [Part] {
+        match self.count {
+            Some(_) => &[Part::One],
+            None => BOTH_PARTS
+        }
+    }
+
+    fn reference_parts(&self) -> &'static [Part] {
+        Solution::parts(self)
+    }
+
+    fn reference_part_one(&self, numbers: &Self::Input<'_>) -> Option<Product<T>> {
+        self.answer_slowly(numbers, self.count.unwrap_or(2))
     }
-    match second_result {
-        None => println!("No match!"),
-        Some(products) => println!("Result: {}", products.0 * products.1 * products.2)
+
+    fn reference_part_two(&self, numbers: &Self::Input<'_>) -> Option<Product<T>> {
+        self.answer_slowly(numbers, 3)
     }
 }
 
@@ -33,21 +352,90 @@ mod tests {
 
     #[test]
     fn should_produce_empty_if_none_sum_for_pair() {
-        assert_eq!(find_pair_summing_to(&vec!(1, 2, 3, 4), 12), None)
+        assert_eq!(find_pair_summing_to(&[1, 2, 3, 4], 12), None)
     }
 
     #[test]
     fn should_produce_pair_if_sum_for_pair() {
-        assert_eq!(find_pair_summing_to(&vec!(1, 2, 3, 4), 7), Some((&3, &4)))
+        assert_eq!(find_pair_summing_to(&[1, 2, 3, 4], 7), Some((&3, &4)))
     }
 
     #[test]
     fn should_produce_empty_if_none_sum_for_triple() {
-        assert_eq!(find_triple_summing_to(&vec!(1, 2, 3, 4), 12), None)
+        assert_eq!(find_triple_summing_to(&[1, 2, 3, 4], 12), None)
     }
 
     #[test]
     fn should_produce_pair_if_sum() {
-        assert_eq!(find_triple_summing_to(&vec!(1, 2, 3, 4), 9), Some((&2, &3, &4)))
+        assert_eq!(find_triple_summing_to(&[1, 2, 3, 4], 9), Some((&2, &3, &4)))
+    }
+
+    #[test]
+    fn should_find_any_number_of_entries_once_each() {
+        assert_eq!(find_k_summing_to(&[9, 1, 7, 3, 5], 4, 24), Some(vec!(&9, &7, &3, &5)));
+        assert_eq!(find_k_summing_to(&[9, 1, 7], 1, 7), Some(vec!(&7)));
+        assert_eq!(find_k_summing_to(&[9, 1, 7], 0, 0), Some(vec!()));
+        assert_eq!(find_k_summing_to(&[9, 1, 7], 4, 17), None);
+        assert_eq!(find_pair_summing_to(&[1010, 3], 2020), None);
+        assert_eq!(find_pair_summing_to(&[1010, 3, 1010], 2020), Some((&1010, &1010)));
+        assert_eq!(find_triple_summing_to(&[u32::MAX, u32::MAX, 1, 1], 3), None)
+    }
+
+    #[test]
+    fn should_find_pair_among_many_entries() {
+        let mut numbers = (0..100_000).map(|it| it * 2).collect::<Vec<u32>>();
+        numbers.insert(5, 77_777);
+        assert_eq!(find_pair_summing_to(&numbers, 227_777), Some((&77_777, &150_000)))
+    }
+
+    #[test]
+    fn should_list_every_sum_with_repeated_values() {
+        let numbers = [1010, 2015, 1010, 5, 1010, 2015];
+        assert_eq!(k_sum_values(&numbers, 2, 2020).collect::<Vec<Vec<u32>>>(), vec!(vec!(5, 2015), vec!(1010, 1010)));
+        assert_eq!(
+            k_sum_indices(&numbers, 2, 2020).collect::<Vec<Vec<usize>>>(),
+            vec!(vec!(1, 3), vec!(3, 5), vec!(0, 2), vec!(0, 4), vec!(2, 4))
+        );
+        assert_eq!(
+            k_sum_values(&numbers, 3, 3030).collect::<Vec<Vec<u32>>>(),
+            vec!(vec!(5, 1010, 2015), vec!(1010, 1010, 1010))
+        );
+        assert_eq!(k_sum_indices(&numbers, 3, 3030).count(), 7);
+        assert_eq!(k_sum_values(&numbers, 4, 6060).count(), 0);
+        assert_eq!(k_sum_indices(&numbers, 0, 0).collect::<Vec<Vec<usize>>>(), vec!(Vec::<usize>::new()))
+    }
+
+    #[test]
+    fn should_solve_with_signed_and_wide_amounts() {
+        let day = DayOne::new();
+        let numbers = day.parse(vec!("-5", "5000000000", "2025", "-4999997985").into_iter(), &mut Diagnostics::new()).unwrap();
+        assert_eq!(day.part_one(&numbers), Some(Product::Value(-10125)));
+        assert_eq!(day.part_two(&numbers), None);
+        assert_eq!(find_pair_summing_to(&[-3_i128, i128::MAX, 7, i128::MIN + 1], 0), Some((&i128::MAX, &(i128::MIN + 1))))
+    }
+
+    #[test]
+    fn should_report_products_that_overflow() {
+        assert_eq!(ExpenseReport::<u16>::new().part_one(&vec!(1010, 7, 1010)), Some(Product::Overflow(vec!(1010, 1010))));
+        assert_eq!(
+            DayOne::new().part_one(&vec!(5_000_000_000_000_000_000, -4_999_999_999_999_997_980)).map(|it| it.to_string()),
+            Some(String::from("overflow: 5000000000000000000 * -4999999999999997980 does not fit in i64"))
+        );
+        assert_eq!(
+            checked_product(&[&4_000_000_000_u32, &2]).to_string(),
+            "overflow: 4000000000 * 2 does not fit in u32"
+        );
+        assert_eq!(ExpenseReport::<u8>::new().part_one(&vec!(1, 2)), None)
+    }
+
+    #[test]
+    fn should_reconcile_other_targets() {
+        let numbers = vec!(40_i64, -10, 70, 25, 50, 15);
+        let report = ExpenseReport::<i64>::reconciling(80, Some(4));
+        assert_eq!(Solution::parts(&report), &[Part::One]);
+        assert_eq!(report.part_one(&numbers), Some(Product::Value(-10 * 25 * 50 * 15)));
+        assert_eq!(report.reference_part_one(&numbers), report.part_one(&numbers));
+        assert_eq!(ExpenseReport::<i64>::reconciling(60, None).part_one(&numbers), Some(Product::Value(-700)));
+        assert_eq!(reconcile(&numbers, 2, 110), Some(Product::Value(2800)))
     }
 }
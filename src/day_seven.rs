@@ -1,17 +1,26 @@
-use crate::file_util::read_non_blank_lines;
-use itertools::Itertools;
+use crate::cancel;
+use crate::file_util::numbered_non_blank_lines;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::Solution;
 
-struct Bag {
-    parents: HashMap<String, u16>,
-    children: Vec<(String, u16)>
+/**
+* A bag colour with the bags it sits inside and the bags it holds, borrowing the colour names
+* from the input.
+*/
+pub struct Bag<'a> {
+    parents: HashMap<&'a str, u16>,
+    children: Vec<(&'a str, u16)>
 }
 
-fn search_parents<'a>(bag: &Bag, tree: &HashMap<String, Bag>, parents: &'a mut HashSet<String>) {
-    for (key, _) in bag.parents.iter() {
-        if !parents.contains(key) {
-            parents.insert(key.clone());
+/**
+* Collects every bag that can eventually contain `bag`.
+*/
+pub fn search_parents<'a>(bag: &Bag<'a>, tree: &HashMap<&'a str, Bag<'a>>, parents: &mut HashSet<&'a str>) {
+    for key in bag.parents.keys() {
+        if parents.insert(key) {
             if let Some(parent_bag) = tree.get(key) {
                 search_parents(parent_bag, tree, parents);
             }
@@ -19,70 +28,179 @@ fn search_parents<'a>(bag: &Bag, tree: &HashMap<String, Bag>, parents: &'a mut H
     }
 }
 
-fn get_children_count(bag: &Bag, tree: &HashMap<String, Bag>) -> usize {
-    bag.children.iter()
-        .map(|(child, count)|
-            usize::from(*count) * (
-                1 + tree
-                    .get(child)
-                    .map(|child_bag| get_children_count(child_bag, tree))
-                    .unwrap_or(0)
-            )
-        )
-        .sum()
+/**
+* Counts the bags inside `bag`, or gives `None` if a bag ends up inside itself, and so would hold
+* without end, if the count does not fit, or if cancelled.
+*/
+pub fn get_children_count<'a>(bag: &Bag<'a>, tree: &HashMap<&'a str, Bag<'a>>) -> Option<usize> {
+    count_inside(bag, tree, &mut HashSet::new())
 }
 
-#[allow(dead_code)]
-pub fn run_day_seven() {
-    let mut bags: HashMap<String, Bag> = HashMap::new();
-
-    read_non_blank_lines("assets/day_seven").for_each(|line| {
-        let mut words = line.split(' ');
-        let name = words.by_ref().take_while(|word| *word != "bags").join(" ");
-        words.by_ref().find(|word| *word != "no");
-
-        let children = words.batching(|children| {
-            if let Some(count) = children.next().and_then(|c| u16::from_str(c).ok()) {
-                let bag_name = children
-                    .take_while(|word| !word.starts_with("bag"))
-                    .join(" ");
-                Some((bag_name, count))
-            } else {
-                None
-            }
-        }).collect::<Vec<(String, u16)>>();
-
-        children.iter().for_each(|(bag_name, count)|
-            if let Some(bag) = bags.get_mut(bag_name) {
-                bag.parents.insert(name.clone(), *count);
-            } else {
-                let mut parents = HashMap::new();
-                parents.insert(name.clone(), *count);
-                bags.insert(bag_name.clone(), Bag { parents, children: Vec::new() });
-            }
-        );
-
-        if let Some(parent) = bags.get_mut(&name) {
-            parent.children = children;
-        } else {
-            bags.insert(name, Bag { parents: HashMap::new(), children });
+/**
+* Counts the bags inside `bag`, where `visiting` holds the bags it is itself inside.
+*/
+fn count_inside<'a>(bag: &Bag<'a>, tree: &HashMap<&'a str, Bag<'a>>, visiting: &mut HashSet<&'a str>) -> Option<usize> {
+    if cancel::requested() {
+        return None;
+    }
+    bag.children.iter().try_fold(0_usize, |total, (child, count)| {
+        if !visiting.insert(child) {
+            return None;
         }
-    });
+        let inside = match tree.get(child) {
+            Some(child_bag) => count_inside(child_bag, tree, visiting)?,
+            None => 0
+        };
+        visiting.remove(child);
+        total.checked_add(usize::from(*count).checked_mul(inside.checked_add(1)?)?)
+    })
+}
 
-    let mut result = HashSet::new();
-    if let Some(shiny_gold) = bags.get("shiny gold") {
-        search_parents(shiny_gold, &bags, &mut result);
+/**
+* Reads the bags listed after `contain`, such as `1 bright white bag, 2 muted yellow bags.`
+*/
+fn parse_contents<'a>(number: usize, line: &'a str, contents: &'a str) -> Result<Vec<(&'a str, u16)>, Diagnostic> {
+    let listed = contents.strip_suffix('.')
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected '.'"))?;
+    if listed == "no other bags" {
+        return Ok(Vec::new());
+    }
+    listed.split(", ")
+        .map(|child| {
+            let (count, rest) = child.split_once(' ')
+                .ok_or_else(|| Diagnostic::at(number, line, child, "expected a count followed by a colour"))?;
+            let count = u16::from_str(count)
+                .map_err(|error| Diagnostic::at(number, line, count, error))?;
+            let name = rest.strip_suffix(" bags").or_else(|| rest.strip_suffix(" bag"))
+                .ok_or_else(|| Diagnostic::at(number, line, rest, "expected a colour followed by 'bag' or 'bags'"))?;
+            Ok((name, count))
+        })
+        .collect()
+}
+
+/**
+* A bag colour with the count of each bag it holds directly.
+*/
+type Rule<'a> = (&'a str, Vec<(&'a str, u16)>);
+
+/**
+* Parses a rule such as `bright white bags contain 1 shiny gold bag.`
+*/
+fn parse_rule(number: usize, line: &str) -> Result<Rule<'_>, Diagnostic> {
+    let (name, contents) = line.split_once(" bags contain ")
+        .ok_or_else(|| Diagnostic::new(number, line, "expected '<colour> bags contain <bags>'"))?;
+    Ok((name, parse_contents(number, line, contents)?))
+}
+
+/**
+* Builds the bag tree keyed by colour, linking each bag to its parents and
+* children.
+*/
+pub fn parse_bags<'a>(lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> HashMap<&'a str, Bag<'a>> {
+    let mut bags: HashMap<&str, Bag> = HashMap::new();
+
+    numbered_non_blank_lines(lines)
+        .filter_map(|(number, line)| diagnostics.check(parse_rule(number, line)))
+        .for_each(|(name, children)| {
+            children.iter().for_each(|(bag_name, count)| {
+                bags.entry(bag_name)
+                    .or_insert_with(|| Bag { parents: HashMap::new(), children: Vec::new() })
+                    .parents
+                    .insert(name, *count);
+            });
+
+            bags.entry(name)
+                .or_insert_with(|| Bag { parents: HashMap::new(), children: Vec::new() })
+                .children = children;
+        });
+    bags
+}
+
+pub struct DaySeven;
+
+impl Solution for DaySeven {
+    type Input<'a> = HashMap<&'a str, Bag<'a>>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(parse_bags(lines, diagnostics))
+    }
+
+    fn part_one(&self, bags: &Self::Input<'_>) -> Option<usize> {
+        let mut result = HashSet::new();
+        search_parents(bags.get("shiny gold")?, bags, &mut result);
+        Some(result.len())
+    }
+
+    fn part_two(&self, bags: &Self::Input<'_>) -> Option<usize> {
+        get_children_count(bags.get("shiny gold")?, bags)
     }
-    let result2 = bags.get("shiny gold").map(|b| get_children_count(b, &bags)).unwrap_or(0);
-    println!("Result: {} {}", result.len(), result2);
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::day_seven::*;
+
+    #[test]
+    fn should_count_bags_around_and_inside_shiny_gold() {
+        let mut diagnostics = Diagnostics::new();
+        let bags = parse_bags(vec!(
+            "light red bags contain 1 bright white bag, 2 muted yellow bags.",
+            "dark orange bags contain 3 bright white bags, 4 muted yellow bags.",
+            "bright white bags contain 1 shiny gold bag.",
+            "muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.",
+            "shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.",
+            "dark olive bags contain 3 faded blue bags, 4 dotted black bags.",
+            "vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.",
+            "faded blue bags contain no other bags.",
+            "dotted black bags contain no other bags."
+        ).into_iter(), &mut diagnostics);
+        assert!(diagnostics.rejected().is_empty());
+        assert_eq!((DaySeven.part_one(&bags), DaySeven.part_two(&bags)), (Some(4), Some(32)))
+    }
 
-    /**
-    * Struggled with time ...
-    */
     #[test]
-    fn should_() {}
+    fn should_point_at_malformed_rules() {
+        let mut diagnostics = Diagnostics::new();
+        let bags = parse_bags(vec!(
+            "shiny gold bags contain 2 dark red bags.",
+            "dark red bags hold 1 dark blue bag.",
+            "dark orange bags contain x bright white bags.",
+            "faded blue bags contain 1 dotted black."
+        ).into_iter(), &mut diagnostics);
+        assert_eq!(DaySeven.part_two(&bags), Some(2));
+        assert_eq!(
+            diagnostics.rejected().iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.width))
+                .collect::<Vec<(usize, usize, usize)>>(),
+            vec!((2, 1, 35), (3, 26, 1), (4, 27, 12))
+        )
+    }
+
+    #[test]
+    fn should_count_bags_nested_deeply_inside_shiny_gold() {
+        let mut diagnostics = Diagnostics::new();
+        let bags = parse_bags(vec!(
+            "shiny gold bags contain 2 dark red bags.",
+            "dark red bags contain 2 dark orange bags.",
+            "dark orange bags contain 2 dark yellow bags.",
+            "dark yellow bags contain 2 dark green bags.",
+            "dark green bags contain 2 dark blue bags.",
+            "dark blue bags contain 2 dark violet bags.",
+            "dark violet bags contain no other bags."
+        ).into_iter(), &mut diagnostics);
+        assert!(diagnostics.rejected().is_empty());
+        assert_eq!((DaySeven.part_one(&bags), DaySeven.part_two(&bags)), (Some(0), Some(126)))
+    }
+
+    #[test]
+    fn should_not_count_bags_inside_themselves() {
+        let mut diagnostics = Diagnostics::new();
+        let bags = parse_bags(vec!(
+            "shiny gold bags contain 1 dark red bag.",
+            "dark red bags contain 1 shiny gold bag."
+        ).into_iter(), &mut diagnostics);
+        assert_eq!((DaySeven.part_one(&bags), DaySeven.part_two(&bags)), (Some(2), None))
+    }
 }
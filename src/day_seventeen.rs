@@ -1,34 +1,41 @@
 use crate::day_seventeen::Block::{Active, Inactive};
-use crate::file_util::read_non_blank_lines;
+use crate::cancel;
+use crate::file_util::numbered_non_blank_lines;
 use std::collections::HashMap;
-use std::iter::repeat;
 use itertools::Itertools;
+use crate::diagnostics::Diagnostics;
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::solution::{Part, Solution};
 
 #[derive(Eq, PartialEq, Clone)]
-enum Block {
+pub enum Block {
     Active, Inactive
 }
 
-struct Board {
+/**
+* An unbounded board of cubes in any number of dimensions. Only active cubes are stored.
+*/
+pub struct Board {
     dimensions: u32,
     blocks: HashMap<Vec<isize>, Block>
 }
 
 impl Board {
-    fn new(dimensions: u32) -> Self {
+    pub fn new(dimensions: u32) -> Self {
         Board {
             dimensions,
             blocks: HashMap::new()
         }
     }
 
-    fn neighbours(&self, coord: &[isize]) -> Vec<(Vec<isize>, &Block)> {
+    pub fn neighbours(&self, coord: &[isize]) -> Vec<(Vec<isize>, &Block)> {
         let mut iterators = Vec::new();
         for i in 0..self.dimensions {
             iterators.push(
-                repeat(0_isize).take(3_usize.pow(i))
-                    .chain(repeat(1).take(3_usize.pow(i)))
-                    .chain(repeat(-1).take(3_usize.pow(i)))
+                std::iter::repeat_n(0_isize, 3_usize.pow(i))
+                    .chain(std::iter::repeat_n(1, 3_usize.pow(i)))
+                    .chain(std::iter::repeat_n(-1, 3_usize.pow(i)))
                     .cycle()
             );
         }
@@ -50,26 +57,29 @@ impl Board {
     }
 
     #[allow(clippy::all)]
-    fn set_active(&mut self, coord: &Vec<isize>) {
+    pub fn set_active(&mut self, coord: &Vec<isize>) {
         self.blocks.insert(coord.clone(), Active);
     }
 
     #[allow(clippy::all)]
-    fn set_inactive(&mut self, coord: &Vec<isize>) {
+    pub fn set_inactive(&mut self, coord: &Vec<isize>) {
         self.blocks.remove(coord);
     }
 
     #[allow(clippy::all)]
-    fn get_block(&self, coord: &Vec<isize>) -> &Block {
+    pub fn get_block(&self, coord: &Vec<isize>) -> &Block {
         self.blocks.get(coord).unwrap_or(&Inactive)
     }
 
-    fn get_active_blocks(&self) -> Vec<&Vec<isize>> {
+    pub fn get_active_blocks(&self) -> Vec<&Vec<isize>> {
         self.blocks.keys().collect()
     }
 }
 
-fn iterate_board(board: &mut Board) {
+/**
+* Runs one cycle of the board.
+*/
+pub fn iterate_board(board: &mut Board) {
     let mut changes = Vec::new();
     let active_blocks = board.get_active_blocks();
     active_blocks.iter().for_each(|block| {
@@ -77,7 +87,7 @@ fn iterate_board(board: &mut Board) {
         let active_neighbours = neighbours.iter()
             .filter(|x| *x.1 == Active)
             .count();
-        if active_neighbours < 2 || active_neighbours > 3 {
+        if !(2..=3).contains(&active_neighbours) {
             changes.push(((*block).clone(), Inactive));
         }
         neighbours.iter()
@@ -97,21 +107,50 @@ fn iterate_board(board: &mut Board) {
     )
 }
 
-#[allow(dead_code)]
-pub fn run_day_seventeen() {
-    let mut board = Board::new(4);
-    read_non_blank_lines("assets/day_seventeen")
-        .enumerate()
-        .for_each(|(x, line)|
-            line.chars().enumerate().for_each(|(y, c)| {
-                if c == '#' {
-                    board.set_active(&vec!(x as isize, y as isize, 0, 0));
-                }
-            })
+pub struct DaySeventeen;
+
+impl Solution for DaySeventeen {
+    type Input<'a> = Vec<(isize, isize)>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        let slice = Grid::parse_skipping(
+            numbered_non_blank_lines(lines),
+            |cube| match cube {
+                '#' => Some(true),
+                '.' => Some(false),
+                _ => None
+            },
+            diagnostics
         );
-    for _ in 0..6 {
-        iterate_board(&mut board);
+        Ok(
+            slice.iter()
+                .filter(|(_, active)| **active)
+                .map(|((column, row), _)| (row, column))
+                .collect()
+        )
+    }
+
+    fn part_one(&self, _: &Self::Input<'_>) -> Option<usize> {
+        None
+    }
+
+    fn part_two(&self, active: &Self::Input<'_>) -> Option<usize> {
+        let mut board = Board::new(4);
+        for (x, y) in active.iter() {
+            board.set_active(&vec!(*x, *y, 0, 0));
+        }
+        for _ in 0..6 {
+            if cancel::requested() {
+                return None;
+            }
+            iterate_board(&mut board);
+        }
+        Some(board.get_active_blocks().len())
+    }
+
+    fn parts(&self) -> &'static [Part] {
+        &[Part::Two]
     }
-    let result_part_two = board.get_active_blocks().iter().count();
-    println!("Result part two {}", result_part_two);
 }
@@ -1,67 +1,74 @@
-use crate::file_util::read_lines;
 use std::collections::{HashSet, HashMap};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::file_util::{records, Record};
+use crate::solution::Solution;
 
-struct BatchIterator<T, F> {
-    iterator: T,
-    transform: F
-}
-
-impl<I, F> BatchIterator<I, F> {
-    fn new(iterator: I, transform: F) -> Self {
-        BatchIterator {
-            iterator, transform
-        }
-    }
-}
-
-impl<T, F, S> Iterator for BatchIterator<T, F> where T: Iterator, F: Fn(&mut T) -> Option<S>  {
-    type Item = S;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        (self.transform)(&mut self.iterator)
-    }
-}
-
-fn aggregate_answers(iterator: &mut impl Iterator<Item = String>) -> Option<(HashSet<char>, HashSet<char>)> {
+/**
+* Returns the questions anyone in the group answered and the questions everyone
+* answered.
+*/
+pub fn aggregate_answers(group: &Record) -> (HashSet<char>, HashSet<char>) {
     let mut combined: HashMap<char, usize> = HashMap::new();
     let mut count = 0;
-    for line in iterator {
-        if line.is_empty() {
-            break;
-        }
+    for line in group.lines() {
         count += 1;
         line.chars().for_each(|x| {
             combined.insert(x, combined.get(&x).map_or(1, |y| y + 1));
         });
     }
-    if count == 0 {
-        None
-    } else {
-        Some(
-            (
-                combined.keys().copied().collect(),
-                combined.iter()
-                    .filter_map(|(x, y)| if *y == count { Some(*x) } else { None })
-                    .collect()
-            )
-        )
+    (
+        combined.keys().copied().collect(),
+        combined.iter()
+            .filter_map(|(x, y)| if *y == count { Some(*x) } else { None })
+            .collect()
+    )
+}
+
+/**
+* Checks that a person's answers are all questions `a` to `z`.
+*/
+fn check_answers(number: usize, line: &str) -> Result<(), Diagnostic> {
+    match line.char_indices().find(|(_, question)| !question.is_ascii_lowercase()) {
+        Some((offset, question)) => Err(Diagnostic::at(
+            number,
+            line,
+            &line[offset..offset + question.len_utf8()],
+            "expected questions 'a' to 'z'"
+        )),
+        None => Ok(())
     }
 }
 
-#[allow(dead_code)]
-pub fn run_day_six() {
-    let mut result = 0;
-    let mut result_two = 0;
-    BatchIterator::new(
-        read_lines("assets/day_six"),
-        aggregate_answers
-    )
-        .for_each(|(first, second)| {
-            result += first.len();
-            result_two += second.len();
-        });
+pub struct DaySix;
 
-    println!("Result: {} {}", result, result_two);
+impl Solution for DaySix {
+    type Input<'a> = Vec<(HashSet<char>, HashSet<char>)>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(
+            records(lines)
+                .filter_map(|group| {
+                    let answered = Record {
+                        lines: group.lines.into_iter()
+                            .filter(|(number, line)| diagnostics.check(check_answers(*number, line)).is_some())
+                            .collect()
+                    };
+                    (!answered.lines.is_empty()).then(|| aggregate_answers(&answered))
+                })
+                .collect()
+        )
+    }
+
+    fn part_one(&self, groups: &Self::Input<'_>) -> Option<usize> {
+        Some(groups.iter().map(|(anyone, _)| anyone.len()).sum())
+    }
+
+    fn part_two(&self, groups: &Self::Input<'_>) -> Option<usize> {
+        Some(groups.iter().map(|(_, everyone)| everyone.len()).sum())
+    }
 }
 
 #[cfg(test)]
@@ -71,14 +78,14 @@ mod tests {
 
     #[test]
     fn should_count_answers_in_groups() {
-        let answers: Vec<(HashSet<char>, HashSet<char>)> = BatchIterator::new(vec!(
-            String::from("ab"),
-            String::from("bc"),
-            String::from("d"),
-            String::from(""),
-            String::from("a"),
-            String::from("ab")
-        ).into_iter(), aggregate_answers).collect();
+        let answers: Vec<(HashSet<char>, HashSet<char>)> = records(vec!(
+            "ab",
+            "bc",
+            "d",
+            "",
+            "a",
+            "ab"
+        ).into_iter()).map(|group| aggregate_answers(&group)).collect();
         let first = answers.first().unwrap();
         let second = answers.last().unwrap();
         assert_eq!(answers.len(), 2);
@@ -87,4 +94,17 @@ mod tests {
         assert_eq!(second.0, HashSet::from_iter(vec!('a', 'b').into_iter()));
         assert_eq!(second.1, HashSet::from_iter(vec!('a').into_iter()))
     }
+
+    #[test]
+    fn should_leave_out_people_with_unknown_questions() {
+        let mut diagnostics = Diagnostics::new();
+        let answers = DaySix.parse(vec!("ab", "a1", "", "A").into_iter(), &mut diagnostics).unwrap();
+        assert_eq!((DaySix.part_one(&answers), DaySix.part_two(&answers)), (Some(2), Some(2)));
+        assert_eq!(
+            diagnostics.rejected().iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column))
+                .collect::<Vec<(usize, usize)>>(),
+            vec!((2, 2), (4, 1))
+        )
+    }
 }
@@ -1,82 +1,121 @@
-use crate::file_util::read_non_blank_lines;
+use crate::cancel;
+use crate::file_util::numbered_non_blank_lines;
 use std::str::FromStr;
 use std::collections::HashSet;
 use itertools::Itertools;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::Solution;
 
-/**
+/*
 *   Just typed this out and ran. Would like to refactor, but no time.
 */
 
-type Rule = [[u16; 2]; 2];
-
-#[allow(dead_code)]
-pub fn run_day_sixteen() {
-    let mut lines = read_non_blank_lines("assets/day_sixteen");
-    let rules = read_rules(&mut lines);
-    let your_ticket = lines.next().map(|x| parse_ticket(x.as_str()));
-    lines.next();
-    let mut other_tickets: Vec<Vec<u16>> = lines
-        .map(|x| parse_ticket(x.as_str()))
-        .collect();
-    let bad_tickets: Vec<(usize, u16)> = get_bad_tickets(&other_tickets, &rules);
-    let part_one: usize = bad_tickets.iter().map(|t| t.1 as usize).sum();
-    for (index, _) in bad_tickets.iter().rev() {
-        other_tickets.remove(*index);
+/**
+* Two inclusive ranges, either of which a value may fall in.
+*/
+pub type Rule = [[u16; 2]; 2];
+
+pub struct Notes {
+    pub rules: Vec<Rule>,
+    pub your_ticket: Option<Vec<u16>>,
+    pub other_tickets: Vec<Vec<u16>>
+}
+
+pub struct DaySixteen;
+
+impl Solution for DaySixteen {
+    type Input<'a> = Notes;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        let mut lines = numbered_non_blank_lines(lines);
+        let rules = read_rules(&mut lines, diagnostics);
+        let your_ticket = lines.next()
+            .and_then(|(number, line)| diagnostics.check(parse_ticket(number, line)));
+        lines.next();
+        let other_tickets = lines
+            .filter_map(|(number, line)| diagnostics.check(parse_ticket(number, line)))
+            .collect();
+        Ok(Notes { rules, your_ticket, other_tickets })
+    }
+
+    fn part_one(&self, notes: &Self::Input<'_>) -> Option<usize> {
+        Some(
+            get_bad_tickets(&notes.other_tickets, &notes.rules)
+                .iter()
+                .map(|t| t.1 as usize)
+                .sum()
+        )
     }
-    println!("Part One: {}", part_one);
-
-    let mut index_hashes: Vec<HashSet<usize>> = rules.iter().map(|rule| {
-        let mut possible_columns = (0..rules.len()).collect::<HashSet<usize>>();
-        for ticket in other_tickets.iter() {
-            for (index, value) in ticket.iter().enumerate() {
-                if !satisfies_rule(*value, rule) {
-                    possible_columns.remove(&index);
-                    if possible_columns.len() == 1 {
-                        return possible_columns;
+
+    fn part_two(&self, notes: &Self::Input<'_>) -> Option<usize> {
+        let rules = &notes.rules;
+        let mut other_tickets = notes.other_tickets.clone();
+        for (index, _) in get_bad_tickets(&other_tickets, rules).iter().rev() {
+            other_tickets.remove(*index);
+        }
+
+        let mut index_hashes: Vec<HashSet<usize>> = rules.iter().map(|rule| {
+            let mut possible_columns = (0..rules.len()).collect::<HashSet<usize>>();
+            for ticket in other_tickets.iter() {
+                for (index, value) in ticket.iter().enumerate() {
+                    if !satisfies_rule(*value, rule) {
+                        possible_columns.remove(&index);
+                        if possible_columns.len() == 1 {
+                            return possible_columns;
+                        }
                     }
                 }
             }
-        }
-        possible_columns
-    }).collect();
-
-    while index_hashes.iter().any(|x| x.len() > 1) {
-        for i in 0 .. rules.len() {
-            let indexes = index_hashes.iter()
-                .enumerate()
-                .filter(|(_, x)| x.contains(&i))
-                .map(|(idx, _)| idx)
-                .collect::<Vec<usize>>();
-            if indexes.len() == 1 {
-                index_hashes[indexes[0]].clear();
-                index_hashes[indexes[0]].insert(i);
+            possible_columns
+        }).collect();
+
+        while index_hashes.iter().any(|x| x.len() > 1) {
+            if cancel::requested() {
+                return None;
+            }
+            let before = index_hashes.clone();
+            for i in 0 .. rules.len() {
+                let indexes = index_hashes.iter()
+                    .enumerate()
+                    .filter(|(_, x)| x.contains(&i))
+                    .map(|(idx, _)| idx)
+                    .collect::<Vec<usize>>();
+                if indexes.len() == 1 {
+                    index_hashes[indexes[0]].clear();
+                    index_hashes[indexes[0]].insert(i);
+                }
+            }
+            let to_clear = index_hashes.iter()
+                .filter(|y| y.len() == 1)
+                .map(|x| *x.iter().next().unwrap())
+                .collect_vec();
+            for v in to_clear.iter() {
+                index_hashes.iter_mut().for_each(|z| {
+                   if z.len() > 1 {
+                       z.remove(v);
+                   }
+                });
+            }
+            if index_hashes == before {
+                return None;
             }
         }
-        let to_clear = index_hashes.iter()
-            .filter(|y| y.len() == 1)
-            .map(|x| *x.iter().next().unwrap())
-            .collect_vec();
-        for v in to_clear.iter() {
-            index_hashes.iter_mut().for_each(|z| {
-               if z.len() > 1 {
-                   z.remove(v);
-               }
-            });
-        }
-    }
 
-    if let Some(ticket) = your_ticket {
-        println!(
-            "Part Two: {}",
+        notes.your_ticket.as_ref().map(|ticket|
             index_hashes.iter().take(6).filter_map(|x|  ticket.get(*x.iter().next().unwrap()).copied())
                 .map(|it| it as usize)
                 .product::<usize>()
-        );
+        )
     }
-
 }
 
-fn get_bad_tickets(tickets: &[Vec<u16>], rules: &[Rule]) -> Vec<(usize, u16)> {
+/**
+* The index of each ticket with a value no rule allows, and that value.
+*/
+pub fn get_bad_tickets(tickets: &[Vec<u16>], rules: &[Rule]) -> Vec<(usize, u16)> {
     tickets
         .iter()
         .enumerate()
@@ -90,38 +129,51 @@ fn get_bad_tickets(tickets: &[Vec<u16>], rules: &[Rule]) -> Vec<(usize, u16)> {
         .collect()
 }
 
-fn satisfies_rule(v: u16, rule: &Rule) -> bool {
+/**
+* Whether `v` is in either of the rule's ranges.
+*/
+pub fn satisfies_rule(v: u16, rule: &Rule) -> bool {
     v >= rule[0][0] && v <= rule[0][1] || v >= rule[1][0] && v <= rule[1][1]
 }
 
-fn parse_ticket(ticket: &str) -> Vec<u16> {
-    ticket.split(',').filter_map(|t| u16::from_str(t).ok()).collect()
+/**
+* Parses a comma separated ticket.
+*/
+pub fn parse_ticket(number: usize, ticket: &str) -> Result<Vec<u16>, Diagnostic> {
+    ticket.split(',')
+        .map(|value| u16::from_str(value).map_err(|error| Diagnostic::at(number, ticket, value, error)))
+        .collect()
 }
 
-fn read_rules(iter: &mut impl Iterator<Item = String>) -> Vec<Rule> {
-    iter.take_while(|l| !l.starts_with("your ticket:"))
-        .filter_map(|line| {
-            let mut split_line = line.split(' ').rev();
-            let first = split_line.next();
-            split_line.next();
-            let second = split_line.next();
-            Some([
-                range_to_arr_tuple(second?)?,
-                range_to_arr_tuple(first?)?
-            ])
-        })
+/**
+* Reads rules up to the `your ticket:` line.
+*/
+pub fn read_rules<'a>(iter: &mut impl Iterator<Item = (usize, &'a str)>, diagnostics: &mut Diagnostics) -> Vec<Rule> {
+    iter.take_while(|(_, line)| !line.starts_with("your ticket:"))
+        .filter_map(|(number, line)| diagnostics.check(parse_rule(number, line)))
         .collect()
 }
 
-fn range_to_arr_tuple(range: &str) -> Option<[u16; 2]> {
-    range.chars()
-        .enumerate()
-        .find_map(|(i, v)| if v == '-' { Some(i) } else { None })
-        .map(|x| range.split_at(x))
-        .and_then(|(x, y)| Some([
-            u16::from_str(x).ok()?,
-            u16::from_str(&y[1..]).ok()?
-        ]))
+/**
+* Parses a rule such as `class: 1-3 or 5-7`.
+*/
+pub fn parse_rule(number: usize, line: &str) -> Result<Rule, Diagnostic> {
+    let (_, ranges) = line.split_once(": ")
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected ': ' after the field name"))?;
+    let (first, second) = ranges.split_once(" or ")
+        .ok_or_else(|| Diagnostic::at(number, line, ranges, "expected two ranges separated by 'or'"))?;
+    Ok([
+        parse_range(number, line, first)?,
+        parse_range(number, line, second)?
+    ])
+}
+
+fn parse_range(number: usize, line: &str, range: &str) -> Result<[u16; 2], Diagnostic> {
+    let (low, high) = range.split_once('-')
+        .ok_or_else(|| Diagnostic::at(number, line, range, "expected a range such as 1-3"))?;
+    let parse_bound = |bound: &str| u16::from_str(bound)
+        .map_err(|error| Diagnostic::at(number, line, bound, error));
+    Ok([parse_bound(low)?, parse_bound(high)?])
 }
 
 #[cfg(test)]
@@ -130,13 +182,15 @@ mod tests {
 
     #[test]
     fn should_read_rules() {
+        let mut diagnostics = Diagnostics::new();
         let result = read_rules(
             &mut vec!(
-                String::from("type: 32-55 or 65-968"),
-                String::from("wagon: 39-642 or 660-955"),
-                String::from("zone: 41-567 or 578-959"),
-                String::from("your ticket:")
-            ).into_iter()
+                (1, "type: 32-55 or 65-968"),
+                (2, "wagon: 39-642 or 660-955"),
+                (3, "zone: 41-567 or 578-959"),
+                (4, "your ticket:")
+            ).into_iter(),
+            &mut diagnostics
         );
         assert_eq!(
             result,
@@ -145,6 +199,15 @@ mod tests {
                 [[39u16, 642u16], [660u16, 955u16]],
                 [[41u16, 567u16], [578u16, 959u16]]
             ]
-        )
+        );
+        assert!(diagnostics.rejected().is_empty())
+    }
+
+    #[test]
+    fn should_point_at_bad_range() {
+        let diagnostic = parse_rule(1, "class: 1-3 or 5-x7").err().unwrap();
+        assert_eq!((diagnostic.column, diagnostic.width), (17, 2));
+        let diagnostic = parse_ticket(2, "7,,14").err().unwrap();
+        assert_eq!((diagnostic.column, diagnostic.width), (3, 1))
     }
 }
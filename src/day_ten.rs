@@ -1,21 +1,62 @@
-use crate::file_util::read_non_blank_lines;
-use std::str::FromStr;
+use std::collections::HashMap;
+use crate::file_util::parse_lines_skipping;
+use crate::diagnostics::Diagnostics;
+use crate::error::Error;
+use crate::solution::{Part, Solution, BOTH_PARTS};
 
-#[allow(dead_code)]
-pub fn run_day_ten() {
-    let mut input = read_non_blank_lines("assets/day_ten")
-        .filter_map(|x| usize::from_str(x.as_str()).ok())
-        .collect::<Vec<usize>>();
-    input.sort_unstable();
-    let result = find_jolt_differences(&mut input);
-    println!("Result Task 1: {}", result[0] * result[1]);
+pub struct DayTen;
 
-    let result2 = get_number_of_arrangements(&input);
-    println!("Result Task 2: {}", result2);
+impl Solution for DayTen {
+    type Input<'a> = Vec<usize>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        let mut input: Vec<usize> = parse_lines_skipping(lines, diagnostics);
+        input.sort_unstable();
+        Ok(input)
+    }
+
+    fn part_one(&self, input: &Self::Input<'_>) -> Option<usize> {
+        let result = find_jolt_differences(input);
+        Some(result[0] * result[1])
+    }
+
+    fn part_two(&self, input: &Self::Input<'_>) -> Option<usize> {
+        Some(get_number_of_arrangements(input))
+    }
+
+    fn reference_parts(&self) -> &'static [Part] {
+        BOTH_PARTS
+    }
+
+    fn reference_part_one(&self, input: &Self::Input<'_>) -> Option<usize> {
+        let mut chain = input.clone();
+        chain.insert(0, 0);
+        chain.push(chain[chain.len() - 1] + 3);
+        let count = |gap: usize| chain.windows(2).filter(|pair| pair[1] - pair[0] == gap).count();
+        Some(count(1) * count(3))
+    }
+
+    fn reference_part_two(&self, input: &Self::Input<'_>) -> Option<usize> {
+        let mut ways = HashMap::new();
+        ways.insert(0, 1);
+        for adapter in input {
+            let reaching = ways.iter()
+                .filter(|(joltage, _)| *joltage < adapter && adapter - *joltage <= 3)
+                .map(|(_, count)| count)
+                .sum::<usize>();
+            ways.insert(*adapter, reaching);
+        }
+        input.last().and_then(|last| ways.get(last)).copied()
+    }
 }
 
-fn get_number_of_arrangements(input: &[usize]) -> usize {
-    vec!(0_usize).iter().chain(input)
+/**
+* Counts the adapter chains that reach the device. `input` must be sorted.
+*/
+pub fn get_number_of_arrangements(input: &[usize]) -> usize {
+    [0_usize].iter().chain(input)
         .zip(input)
         .fold(
             (0, 0, 1),
@@ -27,9 +68,14 @@ fn get_number_of_arrangements(input: &[usize]) -> usize {
         ).2
 }
 
-fn find_jolt_differences(input: &mut [usize]) -> [usize; 2] {
-    let mut result = [1, 1];
-    input.iter().zip(&input[1..])
+/**
+* Counts the differences of one and three jolts in the sorted chain, from the outlet at 0 to the
+* device, which is always 3 above the last adapter. The step from the outlet to the first
+* adapter is counted like any other.
+*/
+pub fn find_jolt_differences(input: &[usize]) -> [usize; 2] {
+    let mut result = [0, 1];
+    [0_usize].iter().chain(input).zip(input)
         .map(|(x, y)| y - x)
         .for_each(|diff| {
             if diff == 1 {
@@ -69,7 +115,14 @@ mod tests {
             7, 9, 4, 2, 34, 10, 3
         ];
         input.sort_unstable();
-        let result = find_jolt_differences(&mut input);
+        let result = find_jolt_differences(&input);
         assert_eq!(result, [22_usize, 10])
     }
+
+    #[test]
+    fn should_count_the_step_from_the_outlet() {
+        assert_eq!(find_jolt_differences(&[1, 2]), [2, 1]);
+        assert_eq!(find_jolt_differences(&[3, 4]), [1, 2]);
+        assert_eq!(find_jolt_differences(&[2, 3]), [1, 1])
+    }
 }
@@ -1,33 +1,102 @@
-use crate::file_util::read_non_blank_lines;
+use crate::cancel;
+use crate::file_util::numbered_non_blank_lines;
 use std::str::FromStr;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::Solution;
 
-#[allow(dead_code)]
-pub fn run_day_thirteen() {
-    let mut lines = read_non_blank_lines("assets/day_thirteen");
-    let departure_time = lines.next()
-        .and_then(|x| usize::from_str(x.as_str()).ok())
-        .unwrap();
-    let buses = lines.next().unwrap().split(',')
-        .enumerate()
-        .filter(|(_, x)| *x != "x")
-        .filter_map(|(index, x)| Some((index, usize::from_str(x).ok()?)))
-        .collect::<Vec<(usize, usize)>>();
-    let earliest_bus = buses.iter()
-        .map(|(_, bus)| (bus, bus - (departure_time % bus)))
-        .min_by(|bus, other| bus.1.cmp(&other.1))
-        .unwrap();
-    let product: usize = buses.iter().map(|(_, b)| b).product();
-    let result_part_two: usize = buses.iter()
-        .map(| (index, bus)| {
-            let factors = product / bus;
-            index * factors * inverse(factors, *bus)
-        })
-        .sum();
-
-    println!("Result {}", earliest_bus.0 * earliest_bus.1);
-    println!("Result Part 2 {}", product - (result_part_two % product));
+pub struct DayThirteen;
+
+impl Solution for DayThirteen {
+    type Input<'a> = (Option<usize>, Vec<(usize, usize)>);
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        let mut lines = numbered_non_blank_lines(lines);
+        let departure_time = lines.next()
+            .and_then(|(number, line)| diagnostics.check(
+                usize::from_str(line).map_err(|error| Diagnostic::new(number, line, error))
+            ));
+        let buses = lines.next()
+            .and_then(|(number, line)| diagnostics.check(parse_buses(number, line)))
+            .unwrap_or_default();
+        for (number, line) in lines {
+            diagnostics.reject(Diagnostic::new(number, line, "expected only the departure time and the bus IDs"));
+        }
+        Ok((departure_time, buses))
+    }
+
+    fn part_one(&self, (departure_time, buses): &Self::Input<'_>) -> Option<usize> {
+        let departure_time = (*departure_time)?;
+        buses.iter()
+            .map(|(_, bus)| (bus, bus - (departure_time % bus)))
+            .min_by(|bus, other| bus.1.cmp(&other.1))
+            .map(|earliest_bus| earliest_bus.0 * earliest_bus.1)
+    }
+
+    fn part_two(&self, (_, buses): &Self::Input<'_>) -> Option<usize> {
+        if buses.is_empty() {
+            return None;
+        }
+        let product = buses.iter().try_fold(1usize, |product, (_, bus)| product.checked_mul(*bus))?;
+        let result_part_two = buses.iter()
+            .try_fold(0usize, |total, (index, bus)| {
+                let factors = product / bus;
+                total.checked_add(index.checked_mul(factors)?.checked_mul(inverse(factors, *bus)?)?)
+            })?;
+        Some(product - (result_part_two % product))
+    }
+}
+
+/**
+* Parses the bus IDs such as `7,13,x,x,59`, keeping each bus with its offset in the list and
+* skipping the buses marked `x`.
+*/
+fn parse_buses(number: usize, line: &str) -> Result<Vec<(usize, usize)>, Diagnostic> {
+    let mut buses = Vec::new();
+    for (index, field) in line.split(',').enumerate().filter(|(_, field)| *field != "x") {
+        let bus = usize::from_str(field).map_err(|error| Diagnostic::at(number, line, field, error))?;
+        if bus == 0 {
+            return Err(Diagnostic::at(number, line, field, "a bus ID must be at least 1"));
+        }
+        buses.push((index, bus));
+    }
+    Ok(buses)
+}
+
+/**
+* The modular inverse of `x` modulo `y`, or `None` if there is none or the search is cancelled.
+*/
+pub fn inverse(x: usize, y: usize) -> Option<usize> {
+    if y == 1 {
+        return Some(0);
+    }
+    let x = x % y;
+    (1..y).take_while(|_| !cancel::requested())
+        .find(|z| z.checked_mul(x).is_some_and(|product| product % y == 1))
 }
 
-fn inverse(x: usize, y: usize) -> usize {
-    (1..y).find(|z| (z * x).rem_euclid(y) == 1).unwrap_or(0)
+#[cfg(test)]
+mod tests {
+    use crate::day_thirteen::*;
+
+    #[test]
+    fn should_point_at_bad_bus_ids() {
+        let mut diagnostics = Diagnostics::new();
+        let notes = DayThirteen.parse(vec!("939", "7,13,x,0,y", "17").into_iter(), &mut diagnostics).unwrap();
+        assert_eq!(notes, (Some(939), Vec::new()));
+        assert_eq!(
+            diagnostics.rejected().iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.width))
+                .collect::<Vec<(usize, usize, usize)>>(),
+            vec!((2, 8, 1), (3, 1, 2))
+        )
+    }
+
+    #[test]
+    fn should_not_overflow_on_large_bus_ids() {
+        let notes = DayThirteen.parse(vec!("1", "1000000007,x,998244353,1000000009").into_iter(), &mut Diagnostics::new()).unwrap();
+        assert_eq!(DayThirteen.part_two(&notes), None)
+    }
 }
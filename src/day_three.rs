@@ -1,119 +1,136 @@
-use crate::file_util::read_non_blank_lines;
-use itertools::Itertools;
+use crate::file_util::numbered_non_blank_lines;
+use crate::diagnostics::Diagnostics;
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::solution::Solution;
 
-const BOARD_SIZE: usize = 31;
-
-fn convert_to_collision_vec(iterator: impl Iterator<Item = String>) -> Vec<Vec<usize>> {
-    iterator.map(|line| line
-        .chars()
-        .positions(|column| column == '#')
-        .collect()
-    ).collect()
+/**
+* The map with `true` for each tree, leaving out and recording the rows that do not parse.
+*/
+pub fn convert_to_tree_grid<'a>(iterator: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Grid<bool> {
+    Grid::parse_skipping(
+        numbered_non_blank_lines(iterator),
+        |square| match square {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None
+        },
+        diagnostics
+    )
 }
 
-fn calculate_collisions(
-    board_size: usize,
+/**
+* Counts the trees hit going `horizontal_step` right and `vertical_step` down from the top
+* left, with the map repeating to the right.
+*/
+pub fn calculate_collisions(
     horizontal_step: usize,
     vertical_step: usize,
-    slope: &[Vec<usize>]
+    slope: &Grid<bool>
 ) -> usize {
-    slope
-        .iter()
+    (0..slope.height())
         .step_by(vertical_step)
         .enumerate()
-        .fold(
-            0,
-            |count, (index, tree_positions)| {
-                let horizontal = (index * horizontal_step) % board_size;
-                if tree_positions.contains(&horizontal) {
-                    count + 1
-                } else {
-                    count
-                }
-            }
-        )
+        .filter(|(index, row)| {
+            slope.get_wrapping(((index * horizontal_step) as isize, *row as isize)) == Some(&true)
+        })
+        .count()
 }
 
-#[allow(dead_code)]
-pub fn run_day_three() {
-    let lines = convert_to_collision_vec(read_non_blank_lines("assets/day_three"));
-    let result = calculate_collisions(
-        BOARD_SIZE,
-        3,
-        1,
-        &lines
-    );
-    println!("Result Task 1 {}", result);
+pub struct DayThree;
 
-    let second_result: usize = [[1, 1], [1, 5], [1, 7], [2, 1]]
-        .iter()
-        .map(|slope|
-            calculate_collisions(
-                BOARD_SIZE,
-                slope[1],
-                slope[0],
-                &lines
-            )
-        )
-        .product();
+impl Solution for DayThree {
+    type Input<'a> = Grid<bool>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(convert_to_tree_grid(lines, diagnostics))
+    }
 
-    println!("Result Task 2 {}", second_result * result)
+    fn part_one(&self, slope: &Self::Input<'_>) -> Option<usize> {
+        Some(calculate_collisions(3, 1, slope))
+    }
+
+    fn part_two(&self, slope: &Self::Input<'_>) -> Option<usize> {
+        Some(
+            [[1, 1], [1, 3], [1, 5], [1, 7], [2, 1]]
+                .iter()
+                .map(|step|
+                    calculate_collisions(
+                        step[1],
+                        step[0],
+                        slope
+                    )
+                )
+                .product()
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day_three::*;
 
+    fn slope(lines: &[&str]) -> Grid<bool> {
+        let mut diagnostics = Diagnostics::new();
+        let slope = convert_to_tree_grid(lines.iter().copied(), &mut diagnostics);
+        assert!(diagnostics.rejected().is_empty());
+        slope
+    }
+
     #[test]
-    fn should_convert_lines_to_collision_vec() {
-        let result = convert_to_collision_vec(
-            vec!(
-                String::from("..##......."),
-                String::from("#...#...#.."),
-                String::from(".#....#..#.")
-            ).into_iter()
-        );
+    fn should_convert_lines_to_tree_grid() {
+        let result = slope(&["..##.......", "#...#...#..", ".#....#..#."]);
+        assert_eq!((result.width(), result.height()), (11, 3));
         assert_eq!(
-            vec!(
-                vec!(2, 3),
-                vec!(0, 4, 8),
-                vec!(1, 6, 9)
-            ),
-            result
+            result.iter().filter(|(_, tree)| **tree).map(|(point, _)| point).collect::<Vec<(isize, isize)>>(),
+            vec!((2, 0), (3, 0), (0, 1), (4, 1), (8, 1), (1, 2), (6, 2), (9, 2))
+        )
+    }
+
+    #[test]
+    fn should_skip_rows_that_do_not_parse() {
+        let mut diagnostics = Diagnostics::new();
+        let result = convert_to_tree_grid(vec!("..#", "#.", ".x.", "#..").into_iter(), &mut diagnostics);
+        assert_eq!((result.width(), result.height()), (3, 2));
+        assert_eq!(
+            diagnostics.rejected().iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column))
+                .collect::<Vec<(usize, usize)>>(),
+            vec!((2, 1), (3, 2))
         )
     }
 
     #[test]
     fn should_calculate_collisions_with_horizontal_skip() {
         // we use the exact strings provided by advent here
-        let slope = convert_to_collision_vec(
-            vec!(
-                String::from("..##......."),
-                String::from("#...#...#.."),
-                String::from(".#....#..#."),
-                String::from("..#.#...#.#"),
-                String::from(".#...##..#."),
-                String::from("..#.##....."),
-                String::from(".#.#.#....#"),
-                String::from(".#........#"),
-                String::from("#.##...#..."),
-                String::from("#...##....#"),
-                String::from(".#..#...#.#")
-            ).into_iter()
-        );
-        let result = calculate_collisions(11, 3, 1, &slope);
+        let slope = slope(&[
+            "..##.......",
+            "#...#...#..",
+            ".#....#..#.",
+            "..#.#...#.#",
+            ".#...##..#.",
+            "..#.##.....",
+            ".#.#.#....#",
+            ".#........#",
+            "#.##...#...",
+            "#...##....#",
+            ".#..#...#.#"
+        ]);
+        let result = calculate_collisions(3, 1, &slope);
         assert_eq!(result, 7)
     }
 
     #[test]
     fn should_calculate_collisions_with_vertical_skip() {
-        let result = calculate_collisions(11, 1, 2, &[
-            vec!(),
-            vec!(0, 1, 2, 3, 4, 5),
-            vec!(1),
-            vec!(0, 1, 2, 3, 4, 5),
-            vec!(2)
-        ]);
+        let result = calculate_collisions(1, 2, &slope(&[
+            "...........",
+            "######.....",
+            ".#.........",
+            "######.....",
+            "..#........"
+        ]));
         assert_eq!(result, 2)
     }
 }
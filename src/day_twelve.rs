@@ -1,29 +1,59 @@
-use crate::file_util::read_non_blank_lines;
+use crate::file_util::numbered_non_blank_lines;
 use std::str::FromStr;
 use crate::day_twelve::Direction::{Forward, Backward};
 use crate::day_twelve::Heading::{East, West, North, South};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::Solution;
 
 enum Heading { North, East, South, West }
 #[derive(PartialEq, Eq)]
 enum Direction { Forward, Backward }
 
-#[allow(dead_code)]
-pub fn run_day_twelve() {
-    let directions = read_non_blank_lines("assets/day_twelve")
-        .filter_map(|line|
-            Some(
-                (line.chars().next().unwrap(), isize::from_str(&line[1..]).ok()?)
-            )
+/**
+* Parses an instruction such as `F10` into its action and amount.
+*/
+pub fn parse_navigation(number: usize, line: &str) -> Result<(char, isize), Diagnostic> {
+    let action = line.chars().next().unwrap_or_default();
+    let (letter, amount) = line.split_at(action.len_utf8());
+    if !"NESWLRFB".contains(action) {
+        return Err(Diagnostic::at(number, line, letter, "unknown action, expected one of N, E, S, W, L, R, F or B"));
+    }
+    isize::from_str(amount)
+        .map(|amount| (action, amount))
+        .map_err(|error| Diagnostic::at(number, line, amount, error))
+}
+
+pub struct DayTwelve;
+
+impl Solution for DayTwelve {
+    type Input<'a> = Vec<(char, isize)>;
+    type PartOne = isize;
+    type PartTwo = isize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(
+            numbered_non_blank_lines(lines)
+                .filter_map(|(number, line)| diagnostics.check(parse_navigation(number, line)))
+                .collect()
         )
-        .collect::<Vec<(char, isize)>>();
-    let result = solve_part_one(&directions);
-    let result2 = solve_part_two(&directions);
+    }
+
+    fn part_one(&self, directions: &Self::Input<'_>) -> Option<isize> {
+        let result = solve_part_one(directions);
+        Some(result.0.abs() + result.1.abs())
+    }
 
-    println!("Result part 1 {}", result.0.abs() + result.1.abs());
-    println!("Result part 2 {}", result2.0.abs() + result2.1.abs());
+    fn part_two(&self, directions: &Self::Input<'_>) -> Option<isize> {
+        let result = solve_part_two(directions);
+        Some(result.0.abs() + result.1.abs())
+    }
 }
 
-fn solve_part_one(inst: &[(char, isize)]) -> (isize, isize) {
+/**
+* Moves the ship directly, returning its final east and north position.
+*/
+pub fn solve_part_one(inst: &[(char, isize)]) -> (isize, isize) {
     inst.iter()
         .fold((East, (0, 0)), |previous, (instruction, number)| {
             match instruction {
@@ -40,7 +70,10 @@ fn solve_part_one(inst: &[(char, isize)]) -> (isize, isize) {
         }).1
 }
 
-fn solve_part_two(inst: &[(char, isize)]) -> (isize, isize) {
+/**
+* Moves the ship towards the waypoint, returning its final east and north position.
+*/
+pub fn solve_part_two(inst: &[(char, isize)]) -> (isize, isize) {
     inst.iter()
         .fold(((10_isize, 1_isize), (0_isize, 0_isize)), |(way_point, ship), (instruction, number)| {
             match instruction {
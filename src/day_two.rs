@@ -1,33 +1,61 @@
-use crate::file_util::read_non_blank_lines;
+use crate::file_util::numbered_non_blank_lines;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::solution::Solution;
 
-struct PasswordPolicy {
-    at_least_length: usize,
-    at_most_length: usize,
-    letter: char,
-    password: String
+pub struct PasswordPolicy<'a> {
+    pub at_least_length: usize,
+    pub at_most_length: usize,
+    pub letter: char,
+    pub password: &'a str
 }
 
-fn parse_password_file(lines: impl Iterator<Item = String>) -> impl Iterator<Item = PasswordPolicy> {
-    lines.filter_map(|line| {
-        let mut split_password = line
-            .splitn(4, |split_on| split_on == ' ' || split_on == '-');
-
-        let at_least_length = split_password.next()?.parse::<usize>().ok()?;
-        let at_most_length = split_password.next()?.parse::<usize>().ok()?;
-        let letter = split_password.next()?.chars().next()?;
-        let password = split_password.next()?.to_owned();
-        Some(
-            PasswordPolicy {
-                at_least_length,
-                at_most_length,
-                letter,
-                password
-            }
-        )
-    })
+/**
+* Parses a line such as `1-3 a: abcde`. The numbers are kept as written: the first task reads
+* them as counts, and the second checks them as positions when it evaluates the policy.
+*/
+pub fn parse_password_policy(number: usize, line: &str) -> Result<PasswordPolicy<'_>, Diagnostic> {
+    let mut split_password = line
+        .splitn(4, [' ', '-']);
+
+    let at_least = split_password.next().unwrap_or(line);
+    let at_least_length = at_least.parse::<usize>()
+        .map_err(|error| Diagnostic::at(number, line, at_least, error))?;
+    let at_most = split_password.next()
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected '-' followed by the highest count"))?;
+    let at_most_length = at_most.parse::<usize>()
+        .map_err(|error| Diagnostic::at(number, line, at_most, error))?;
+    let letter_rule = split_password.next()
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected a letter"))?;
+    let mut letters = letter_rule.chars();
+    let letter = match (letters.next(), letters.next(), letters.next()) {
+        (Some(letter), Some(':'), None) => letter,
+        _ => return Err(Diagnostic::at(number, line, letter_rule, "expected a single letter followed by ':'"))
+    };
+    let password = split_password.next()
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected a password"))?;
+    Ok(
+        PasswordPolicy {
+            at_least_length,
+            at_most_length,
+            letter,
+            password
+        }
+    )
+}
+
+/**
+* Parses every non blank line, numbering lines from one.
+*/
+pub fn parse_password_file<'a>(lines: impl Iterator<Item = &'a str>) -> impl Iterator<Item = Result<PasswordPolicy<'a>, Diagnostic>> {
+    numbered_non_blank_lines(lines)
+        .map(|(number, line)| parse_password_policy(number, line))
 }
 
-fn is_valid_for_task_one(policy: &PasswordPolicy) -> bool {
+/**
+* The letter appears between the two counts, inclusive.
+*/
+pub fn is_valid_for_task_one(policy: &PasswordPolicy) -> bool {
     let char_count = policy.password
         .chars()
         .filter(|curr| curr == &policy.letter)
@@ -35,145 +63,187 @@ fn is_valid_for_task_one(policy: &PasswordPolicy) -> bool {
     char_count <= policy.at_most_length && char_count >= policy.at_least_length
 }
 
-fn is_valid_for_task_two(policy: &PasswordPolicy) -> bool {
-    let mut iterator = policy.password.chars();
-    let is_first = iterator
-        .nth(policy.at_least_length - 1)
-        .map_or(false, |letter| letter == policy.letter);
-    let is_second = iterator
-        .nth(policy.at_most_length - policy.at_least_length - 1)
-        .map_or(false, |letter| letter == policy.letter);
-    is_first ^ is_second
+/**
+* Whether the password has the letter at `position`, counting from one. Position zero and
+* positions past the end never hold it.
+*/
+fn holds_letter(policy: &PasswordPolicy, position: usize) -> bool {
+    position.checked_sub(1).and_then(|index| policy.password.chars().nth(index)) == Some(policy.letter)
 }
 
-#[allow(dead_code)]
-pub fn run_day_two() {
-    let mut result = [0, 0];
-    let number_valid = parse_password_file(read_non_blank_lines("assets/day_two"))
-        .fold(&mut result, |prev, policy| {
-            if is_valid_for_task_one(&policy) {
-                prev[0] += 1;
-            }
-            if is_valid_for_task_two(&policy) {
-                prev[1] += 1;
-            }
-            prev
-        });
-    println!("Number valid: {} {}", number_valid[0], number_valid[1])
+/**
+* Exactly one of the two positions, counting from one, holds the letter. When both are the same
+* position it is counted twice, so the password is never valid. A position of zero or past the
+* end of the password never holds the letter.
+*/
+pub fn is_valid_for_task_two(policy: &PasswordPolicy) -> bool {
+    holds_letter(policy, policy.at_least_length) != holds_letter(policy, policy.at_most_length)
+}
+
+pub struct DayTwo;
+
+impl Solution for DayTwo {
+    type Input<'a> = Vec<PasswordPolicy<'a>>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+        Ok(
+            parse_password_file(lines)
+                .filter_map(|policy| diagnostics.check(policy))
+                .collect()
+        )
+    }
+
+    fn part_one(&self, policies: &Self::Input<'_>) -> Option<usize> {
+        Some(policies.iter().filter(|policy| is_valid_for_task_one(policy)).count())
+    }
+
+    fn part_two(&self, policies: &Self::Input<'_>) -> Option<usize> {
+        Some(policies.iter().filter(|policy| is_valid_for_task_two(policy)).count())
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
     use crate::day_two::*;
-    use itertools::Itertools;
 
     #[test]
     fn should_parse_valid_password_file() {
         let parsed = parse_password_file(
-            vec!("3-4 q: wqqkzwqgkqkk".to_owned(), "1-2 k: aaa".to_owned(), "1-2 a: aaa".to_owned()).into_iter()
-        ).collect_vec();
+            vec!("3-4 q: wqqkzwqgkqkk", "1-2 k: aaa", "1-2 a: aaa").into_iter()
+        ).collect::<Result<Vec<PasswordPolicy>, Diagnostic>>().unwrap();
         let first = parsed.last();
         assert_eq!(parsed.len(), 3);
-        assert_eq!(first.map(|p| p.password.as_str()).get_or_insert(""), &"aaa");
+        assert_eq!(first.map(|p| p.password).get_or_insert(""), &"aaa");
         assert_eq!(first.map(|p| p.letter).get_or_insert(' '), &'a');
         assert_eq!(first.map(|p| p.at_least_length).get_or_insert(0), &mut 1);
         assert_eq!(first.map(|p| p.at_most_length).get_or_insert(0), &mut 2)
     }
 
+    #[test]
+    fn should_point_at_malformed_part_of_policy() {
+        let rejected = parse_password_file(
+            vec!("1-x a: abc", "", "1-3 ab: abc", "1-3 a:").into_iter()
+        )
+            .filter_map(|policy| policy.err())
+            .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.width))
+            .collect::<Vec<(usize, usize, usize)>>();
+        assert_eq!(rejected, vec!((1, 3, 1), (3, 5, 3), (4, 7, 1)))
+    }
+
+    #[test]
+    fn should_keep_count_policies_that_are_not_positions() {
+        let policies = DayTwo.parse(
+            vec!("0-3 a: bcd", "1-20 a: aaa", "3-1 a: abc").into_iter(),
+            &mut Diagnostics::new()
+        ).unwrap();
+        assert_eq!(policies.len(), 3);
+        assert_eq!((DayTwo.part_one(&policies), DayTwo.part_two(&policies)), (Some(2), Some(2)))
+    }
+
+    #[test]
+    fn should_check_positions_without_underflow() {
+        let policy = |at_least_length, at_most_length, password| PasswordPolicy {
+            at_least_length,
+            at_most_length,
+            letter: 'a',
+            password
+        };
+        assert!(!is_valid_for_task_two(&policy(3, 3, "bca")));
+        assert!(!is_valid_for_task_two(&policy(3, 3, "bcd")));
+        assert!(is_valid_for_task_two(&policy(0, 1, "abc")));
+        assert!(!is_valid_for_task_two(&policy(0, 2, "abc")));
+        assert!(is_valid_for_task_two(&policy(3, 1, "bca")));
+        assert!(is_valid_for_task_two(&policy(1, 40, "abc")))
+    }
+
     #[test]
     fn should_reject_invalid_password_file_for_task_one_due_to_at_least_condition() {
-        assert_eq!(
-            is_valid_for_task_one(&PasswordPolicy{
+        assert!(
+            !is_valid_for_task_one(&PasswordPolicy{
                 at_least_length: 1,
                 at_most_length: 3,
                 letter: 'a',
-                password: "bbbb".to_owned()
-            }),
-            false
+                password: "bbbb"
+            })
         )
     }
 
     #[test]
     fn should_reject_invalid_password_file_for_task_one_due_to_at_most_condition() {
-        assert_eq!(
-            is_valid_for_task_one(&PasswordPolicy{
+        assert!(
+            !is_valid_for_task_one(&PasswordPolicy{
                 at_least_length: 1,
                 at_most_length: 3,
                 letter: 'a',
-                password: "aaaabbbb".to_owned()
-            }),
-            false
+                password: "aaaabbbb"
+            })
         )
     }
 
     #[test]
     fn should_accept_valid_password_file_for_task_one() {
-        assert_eq!(
+        assert!(
             is_valid_for_task_one(&PasswordPolicy{
                 at_least_length: 1,
                 at_most_length: 3,
                 letter: 'a',
-                password: "a".to_owned()
-            }),
-            true
+                password: "a"
+            })
         );
-        assert_eq!(
+        assert!(
             is_valid_for_task_one(&PasswordPolicy{
                 at_least_length: 1,
                 at_most_length: 3,
                 letter: 'a',
-                password: "aaabbbb".to_owned()
-            }),
-            true
+                password: "aaabbbb"
+            })
         )
     }
 
     #[test]
     fn should_accept_valid_password_file_for_task_two() {
-        assert_eq!(
+        assert!(
             is_valid_for_task_two(&PasswordPolicy{
                 at_least_length: 1,
                 at_most_length: 3,
                 letter: 'a',
-                password: "abc".to_owned()
-            }),
-            true
+                password: "abc"
+            })
         );
-        assert_eq!(
+        assert!(
             is_valid_for_task_two(&PasswordPolicy{
                 at_least_length: 2,
                 at_most_length: 3,
                 letter: 'a',
-                password: "dbaf".to_owned()
-            }),
-            true
+                password: "dbaf"
+            })
         )
     }
 
     #[test]
     fn should_reject_invalid_password_file_due_to_both_matching_for_task_two() {
-        assert_eq!(
-            is_valid_for_task_two(&PasswordPolicy{
+        assert!(
+            !is_valid_for_task_two(&PasswordPolicy{
                 at_least_length: 4,
                 at_most_length: 9,
                 letter: 'b',
-                password: "aaabaaaaba".to_owned()
-            }),
-            false
+                password: "aaabaaaaba"
+            })
         );
     }
 
     #[test]
     fn should_reject_invalid_password_file_due_to_neither_matching_for_task_two() {
-        assert_eq!(
-            is_valid_for_task_two(&PasswordPolicy{
+        assert!(
+            !is_valid_for_task_two(&PasswordPolicy{
                 at_least_length: 4,
                 at_most_length: 9,
                 letter: 'b',
-                password: "a".to_owned()
-            }),
-            false
+                password: "a"
+            })
         );
     }
 }
@@ -0,0 +1,163 @@
+use std::fmt;
+use std::path::Path;
+
+/**
+* How a day treats lines it cannot parse. `Lenient` skips them and keeps going, `Strict`
+* fails the whole input once every line has been checked.
+*/
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Mode {
+    Strict, Lenient
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub width: usize,
+    pub text: String,
+    pub reason: String
+}
+
+impl Diagnostic {
+    /**
+    * Rejects a whole line. Lines and columns are numbered from one.
+    */
+    pub fn new(line: usize, text: &str, reason: impl fmt::Display) -> Self {
+        Diagnostic {
+            line,
+            column: 1,
+            width: text.chars().count().max(1),
+            text: String::from(text),
+            reason: reason.to_string()
+        }
+    }
+
+    /**
+    * Rejects `fragment`, which must be a slice of `text`, pointing the caret at it. Anything
+    * else falls back to the whole line.
+    */
+    pub fn at(line: usize, text: &str, fragment: &str, reason: impl fmt::Display) -> Self {
+        let start = (fragment.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+        let found = start.checked_add(fragment.len()).and_then(|end| text.get(start..end));
+        match found {
+            Some(found) if found.as_ptr() == fragment.as_ptr() => Diagnostic {
+                line,
+                column: text[..start].chars().count() + 1,
+                width: fragment.chars().count().max(1),
+                text: String::from(text),
+                reason: reason.to_string()
+            },
+            _ => Diagnostic::new(line, text, reason)
+        }
+    }
+
+    /**
+    * Points just past the end of the line, for input that stops too early.
+    */
+    pub fn at_end(line: usize, text: &str, reason: impl fmt::Display) -> Self {
+        Diagnostic::at(line, text, &text[text.len()..], reason)
+    }
+
+    pub fn render(&self, path: Option<&Path>) -> String {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        format!(
+            "error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}",
+            self.reason,
+            gutter,
+            path.map(|it| it.display().to_string()).unwrap_or_else(|| String::from("<input>")),
+            self.line,
+            self.column,
+            gutter,
+            number,
+            self.text,
+            gutter,
+            " ".repeat(self.column - 1),
+            "^".repeat(self.width)
+        )
+    }
+}
+
+/**
+* Collects the lines a parser rejected while it carries on with the rest of the input.
+*/
+#[derive(Default)]
+pub struct Diagnostics {
+    rejected: Vec<Diagnostic>
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics::default()
+    }
+
+    pub fn reject(&mut self, diagnostic: Diagnostic) {
+        self.rejected.push(diagnostic);
+    }
+
+    /**
+    * Keeps a parsed value, or records why its line was rejected.
+    */
+    pub fn check<T>(&mut self, result: Result<T, Diagnostic>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(diagnostic) => {
+                self.reject(diagnostic);
+                None
+            }
+        }
+    }
+
+    pub fn rejected(&self) -> &[Diagnostic] {
+        &self.rejected
+    }
+
+    pub fn into_rejected(self) -> Vec<Diagnostic> {
+        self.rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::*;
+
+    #[test]
+    fn should_point_at_fragment() {
+        let line = "jmp +x4";
+        let diagnostic = Diagnostic::at(12, line, &line[4..], "invalid digit found in string");
+        assert_eq!(diagnostic.column, 5);
+        assert_eq!(diagnostic.width, 3);
+        assert_eq!(
+            diagnostic.render(Some(Path::new("assets/day_eight"))),
+            "error: invalid digit found in string\n  --> assets/day_eight:12:5\n   |\n12 | jmp +x4\n   |     ^^^"
+        )
+    }
+
+    #[test]
+    fn should_point_past_end_of_line() {
+        let diagnostic = Diagnostic::at_end(1, "1-3", "expected a letter");
+        assert_eq!((diagnostic.column, diagnostic.width), (4, 1))
+    }
+
+    #[test]
+    fn should_fall_back_to_whole_line_for_foreign_fragment() {
+        let diagnostic = Diagnostic::at(1, "abc", &String::from("b"), "unexpected");
+        assert_eq!((diagnostic.column, diagnostic.width), (1, 3))
+    }
+
+    #[test]
+    fn should_fall_back_to_whole_line_for_fragment_before_it() {
+        let line = "mem[8] = 11";
+        let diagnostic = Diagnostic::at(1, &line[4..], &line[..3], "unexpected");
+        assert_eq!((diagnostic.column, diagnostic.width), (1, 7))
+    }
+
+    #[test]
+    fn should_collect_rejected_lines() {
+        let mut diagnostics = Diagnostics::new();
+        assert_eq!(diagnostics.check::<u8>(Ok(1)), Some(1));
+        assert_eq!(diagnostics.check::<u8>(Err(Diagnostic::new(2, "x", "bad"))), None);
+        assert_eq!(diagnostics.rejected().len(), 1)
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::ops::Range;
+use crate::error::Error;
+use crate::random::Random;
+use crate::registry::Entry;
+use crate::solution::Part;
+
+/**
+* A generated input on which a day's solver and its reference solver give different answers.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct Divergence {
+    pub day: u8,
+    pub part: Part,
+    pub seed: u64,
+    pub size: usize,
+    pub answer: Option<String>,
+    pub reference: Option<String>,
+    pub input: String
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |answer: &Option<String>| answer.clone().unwrap_or_else(|| String::from("no answer"));
+        writeln!(
+            f,
+            "Day {} part {} answered {} but the reference answered {}, on seed {} with size {}:",
+            self.day,
+            self.part,
+            describe(&self.answer),
+            describe(&self.reference),
+            self.seed,
+            self.size
+        )?;
+        write!(f, "{}", self.input)
+    }
+}
+
+/**
+* Solves the inputs generated from each seed in turn with both the day's solver and its reference
+* solver, for every part that has one. Returns the first input they disagree on.
+*/
+pub fn cross_check(entry: &Entry, seeds: Range<u64>, size: usize) -> Result<Option<Divergence>, Error> {
+    for seed in seeds {
+        let input = (entry.generator)(&mut Random::new(seed), size);
+        for part in entry.solution.reference_parts() {
+            let (answer, reference) = entry.solution.compare(&input, *part)?;
+            if answer != reference {
+                return Ok(Some(Divergence { day: entry.day, part: *part, seed, size, answer, reference, input }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differential::*;
+    use crate::diagnostics::Diagnostics;
+    use crate::file_util::parse_lines;
+    use crate::generate;
+    use crate::registry::{Solver, DAYS};
+    use crate::solution::Solution;
+
+    #[test]
+    fn should_agree_with_every_reference_solver() {
+        for entry in DAYS.iter().filter(|entry| !entry.solution.reference_parts().is_empty()) {
+            if let Some(divergence) = cross_check(entry, 0..25, 40).unwrap() {
+                panic!("{}", divergence);
+            }
+        }
+    }
+
+    /**
+    * Answers part one with the largest number, where the reference wants the smallest.
+    */
+    struct Largest;
+
+    impl Solution for Largest {
+        type Input<'a> = Vec<u32>;
+        type PartOne = u32;
+        type PartTwo = u32;
+
+        fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, _: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+            parse_lines(lines)
+        }
+
+        fn part_one(&self, numbers: &Self::Input<'_>) -> Option<u32> {
+            numbers.iter().max().copied()
+        }
+
+        fn part_two(&self, _: &Self::Input<'_>) -> Option<u32> {
+            None
+        }
+
+        fn reference_parts(&self) -> &'static [Part] {
+            &[Part::One]
+        }
+
+        fn reference_part_one(&self, numbers: &Self::Input<'_>) -> Option<u32> {
+            numbers.iter().min().copied()
+        }
+    }
+
+    #[test]
+    fn should_report_first_diverging_input() {
+        let entry = Entry { day: 1, name: "largest", solution: Solver::Registered(&Largest), generator: generate::day_one };
+        let divergence = cross_check(&entry, 3..10, 10).unwrap().unwrap();
+        assert_eq!((divergence.part, divergence.seed, divergence.size), (Part::One, 3, 10));
+        assert_eq!(Some(divergence.input), generate::generate(1, 3, 10));
+        assert_ne!(divergence.answer, divergence.reference)
+    }
+}
@@ -0,0 +1,56 @@
+/**
+* The puzzle inputs and the answers manifest, compiled in by the `embed-assets` feature so that
+* the binary runs without an `assets` directory. Without the feature nothing is embedded.
+*/
+#[cfg(feature = "embed-assets")]
+static ASSETS: &[(&str, &str)] = &[
+    ("answers", include_str!("../assets/answers")),
+    ("day_one", include_str!("../assets/day_one")),
+    ("day_two", include_str!("../assets/day_two")),
+    ("day_three", include_str!("../assets/day_three")),
+    ("day_four", include_str!("../assets/day_four")),
+    ("day_five", include_str!("../assets/day_five")),
+    ("day_six", include_str!("../assets/day_six")),
+    ("day_seven", include_str!("../assets/day_seven")),
+    ("day_eight", include_str!("../assets/day_eight")),
+    ("day_nine", include_str!("../assets/day_nine")),
+    ("day_ten", include_str!("../assets/day_ten")),
+    ("day_eleven", include_str!("../assets/day_eleven")),
+    ("day_twelve", include_str!("../assets/day_twelve")),
+    ("day_thirteen", include_str!("../assets/day_thirteen")),
+    ("day_fourteen", include_str!("../assets/day_fourteen")),
+    ("day_fifteen", include_str!("../assets/day_fifteen")),
+    ("day_sixteen", include_str!("../assets/day_sixteen")),
+    ("day_seventeen", include_str!("../assets/day_seventeen")),
+    ("day_eighteen", include_str!("../assets/day_eighteen")),
+    ("day_nineteen", include_str!("../assets/day_nineteen")),
+    ("day_twenty", include_str!("../assets/day_twenty"))
+];
+
+#[cfg(not(feature = "embed-assets"))]
+static ASSETS: &[(&str, &str)] = &[];
+
+/**
+* Whether this build carries its own copy of the assets.
+*/
+pub const ENABLED: bool = cfg!(feature = "embed-assets");
+
+pub fn get(name: &str) -> Option<&'static str> {
+    ASSETS.iter()
+        .find(|(asset, _)| *asset == name)
+        .map(|(_, contents)| *contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::embedded::*;
+    use crate::registry::DAYS;
+
+    #[test]
+    fn should_embed_every_day_only_with_feature() {
+        for entry in DAYS.iter() {
+            assert_eq!(get(entry.name).is_some(), ENABLED, "{}", entry.name);
+        }
+        assert_eq!(get("answers").is_some(), ENABLED)
+    }
+}
@@ -0,0 +1,99 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::diagnostics::Diagnostic;
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: Option<PathBuf>,
+        line: Option<usize>,
+        source: io::Error
+    },
+    Parse {
+        path: Option<PathBuf>,
+        diagnostics: Vec<Diagnostic>
+    }
+}
+
+impl Error {
+    pub fn io(path: Option<&Path>, line: Option<usize>, source: io::Error) -> Self {
+        Error::Io { path: path.map(Path::to_path_buf), line, source }
+    }
+
+    pub fn rejected(diagnostics: Vec<Diagnostic>) -> Self {
+        Error::Parse { path: None, diagnostics }
+    }
+
+    /**
+    * Attaches the file the error came from, keeping any path already recorded.
+    */
+    pub fn with_path(self, file: &Path) -> Self {
+        match self {
+            Error::Io { path, line, source } => Error::Io {
+                path: path.or_else(|| Some(file.to_path_buf())), line, source
+            },
+            Error::Parse { path, diagnostics } => Error::Parse {
+                path: path.or_else(|| Some(file.to_path_buf())), diagnostics
+            }
+        }
+    }
+}
+
+fn describe_path(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|it| it.display().to_string())
+        .unwrap_or_else(|| String::from("<input>"))
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, line: Some(line), source } =>
+                write!(f, "{}:{}: failed to read: {}", describe_path(path), line, source),
+            Error::Io { path, line: None, source } =>
+                write!(f, "{}: failed to read: {}", describe_path(path), source),
+            Error::Parse { path, diagnostics } => {
+                for diagnostic in diagnostics.iter() {
+                    writeln!(f, "{}", diagnostic.render(path.as_deref()))?;
+                }
+                match diagnostics.len() {
+                    1 => write!(f, "1 line rejected"),
+                    count => write!(f, "{} lines rejected", count)
+                }
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { .. } => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::*;
+
+    #[test]
+    fn should_describe_parse_error_with_location() {
+        let error = Error::rejected(vec!(Diagnostic::new(3, "12a", "invalid digit")))
+            .with_path(Path::new("assets/day_one"));
+        assert_eq!(
+            error.to_string(),
+            "error: invalid digit\n --> assets/day_one:3:1\n  |\n3 | 12a\n  | ^^^\n1 line rejected"
+        )
+    }
+
+    #[test]
+    fn should_keep_original_path() {
+        let error = Error::io(Some(Path::new("first")), None, io::Error::from(io::ErrorKind::NotFound))
+            .with_path(Path::new("second"));
+        assert!(error.to_string().starts_with("first: failed to read: "))
+    }
+}
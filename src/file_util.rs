@@ -1,22 +1,179 @@
+use std::fs;
 use std::io;
-use std::fs::File;
-use std::io::BufRead;
+use std::io::Read;
+use std::iter::from_fn;
 use std::path::Path;
 use std::str::FromStr;
+use std::fmt::Display;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
 
-pub fn read_lines<P>(filename: P) -> impl Iterator<Item = String> where P: AsRef<Path> {
-    let file = File::open(filename).expect("Failed to read file.");
-    io::BufReader::new(file)
-        .lines()
-        .map(|line| line.expect("Failed reading line."))
+/**
+* Checks the whole input is UTF-8, reporting the line of the first bad byte.
+*/
+pub fn decode(bytes: Vec<u8>, path: Option<&Path>) -> Result<String, Error> {
+    String::from_utf8(bytes).map_err(|error| {
+        let valid = &error.as_bytes()[..error.utf8_error().valid_up_to()];
+        let line = valid.iter().filter(|byte| **byte == b'\n').count() + 1;
+        Error::io(path, Some(line), io::Error::new(io::ErrorKind::InvalidData, error.utf8_error()))
+    })
 }
 
-pub fn read_non_blank_lines<P>(filename: P) -> impl Iterator<Item = String> where P: AsRef<Path> {
-    read_lines(filename)
-        .filter(|line| !line.is_empty())
+/**
+* Loads a whole file into one buffer. Lines and records are then borrowed from it with
+* `str::lines` and `records`, so nothing is allocated per line.
+*/
+pub fn read_text<P>(filename: P) -> Result<String, Error> where P: AsRef<Path> {
+    let path = filename.as_ref();
+    let bytes = fs::read(path).map_err(|source| Error::io(Some(path), None, source))?;
+    decode(bytes, Some(path))
 }
 
-pub fn read_lines_as_u32<P>(filename: P) -> impl Iterator<Item = u32> where P: AsRef<Path> {
-    read_non_blank_lines(filename)
-        .map(|line| u32::from_str(line.as_str()).expect("Failed to convert to u32."))
+pub fn read_stdin_text() -> Result<String, Error> {
+    let mut bytes = Vec::new();
+    io::stdin().lock()
+        .read_to_end(&mut bytes)
+        .map_err(|source| Error::io(None, None, source))?;
+    decode(bytes, None)
+}
+
+pub fn non_blank_lines<'a>(lines: impl Iterator<Item = &'a str>) -> impl Iterator<Item = &'a str> {
+    lines.filter(|line| !line.is_empty())
+}
+
+/**
+* Pairs each line with its line number, counting from one.
+*/
+pub fn numbered_lines<'a>(lines: impl Iterator<Item = &'a str>) -> impl Iterator<Item = (usize, &'a str)> {
+    lines
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+}
+
+pub fn numbered_non_blank_lines<'a>(lines: impl Iterator<Item = &'a str>) -> impl Iterator<Item = (usize, &'a str)> {
+    numbered_lines(lines).filter(|(_, line)| !line.is_empty())
+}
+
+/**
+* A run of consecutive non blank lines, each with its line number.
+*/
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Record<'a> {
+    pub lines: Vec<(usize, &'a str)>
+}
+
+impl<'a> Record<'a> {
+    pub fn first_line(&self) -> usize {
+        self.lines[0].0
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.lines.iter().map(|(_, line)| *line)
+    }
+
+    pub fn join(&self, separator: &str) -> String {
+        self.lines().collect::<Vec<&str>>().join(separator)
+    }
+}
+
+/**
+* Splits the input into records separated by blank lines. Trailing whitespace, including the
+* `\r` of a CRLF ending, is trimmed from every line, so a line of spaces is blank too. Several
+* blank lines in a row, or blank lines at either end, never produce an empty record.
+*/
+pub fn records<'a>(lines: impl Iterator<Item = &'a str>) -> impl Iterator<Item = Record<'a>> {
+    let mut lines = numbered_lines(lines).map(|(number, line)| (number, line.trim_end()));
+    from_fn(move || {
+        let record = lines.by_ref()
+            .skip_while(|(_, line)| line.is_empty())
+            .take_while(|(_, line)| !line.is_empty())
+            .collect::<Vec<(usize, &str)>>();
+        if record.is_empty() {
+            None
+        } else {
+            Some(Record { lines: record })
+        }
+    })
+}
+
+/**
+* Parses every non blank line, reporting all of the lines that do not convert.
+*/
+pub fn parse_lines<'a, T>(lines: impl Iterator<Item = &'a str>) -> Result<Vec<T>, Error>
+    where T: FromStr, T::Err: Display {
+    let mut parsed = Vec::new();
+    let mut rejected = Vec::new();
+    for (number, line) in numbered_non_blank_lines(lines) {
+        match T::from_str(line) {
+            Ok(value) => parsed.push(value),
+            Err(reason) => rejected.push(Diagnostic::new(number, line, reason))
+        }
+    }
+    if rejected.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(Error::rejected(rejected))
+    }
+}
+
+/**
+* Like `parse_lines`, but records each line that does not convert and leaves it out.
+*/
+pub fn parse_lines_skipping<'a, T>(lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Vec<T>
+    where T: FromStr, T::Err: Display {
+    numbered_non_blank_lines(lines)
+        .filter_map(|(number, line)| diagnostics.check(
+            T::from_str(line).map_err(|reason| Diagnostic::new(number, line, reason))
+        ))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_util::*;
+
+    #[test]
+    fn should_parse_lines_skipping_blanks() {
+        assert_eq!(parse_lines::<u32>("1\n\n3".lines()).unwrap(), vec!(1, 3))
+    }
+
+    #[test]
+    fn should_report_line_of_bad_number() {
+        match parse_lines::<u32>("1\n\nx3".lines()) {
+            Err(Error::Parse { diagnostics, .. }) => assert_eq!(
+                diagnostics,
+                vec!(Diagnostic::new(3, "x3", "invalid digit found in string"))
+            ),
+            _ => panic!("Expected the third line to be rejected.")
+        }
+    }
+
+    #[test]
+    fn should_skip_and_record_bad_numbers() {
+        let mut diagnostics = Diagnostics::new();
+        assert_eq!(parse_lines_skipping::<u32>("1\nx\n\n3".lines(), &mut diagnostics), vec!(1, 3));
+        assert_eq!(diagnostics.rejected().iter().map(|it| it.line).collect::<Vec<usize>>(), vec!(2))
+    }
+
+    #[test]
+    fn should_split_records_on_blank_lines() {
+        let text = "\r\na b \r\nc\n\n  \n\nd\r\n\n";
+        let records = records(text.lines()).collect::<Vec<Record>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].lines, vec!((2, "a b"), (3, "c")));
+        assert_eq!(records[0].join(" "), "a b c");
+        assert_eq!((records[1].first_line(), records[1].join(" ")), (7, String::from("d")))
+    }
+
+    #[test]
+    fn should_report_missing_file() {
+        let error = read_text("assets/day_zero").unwrap_err();
+        assert!(error.to_string().starts_with("assets/day_zero: failed to read: "))
+    }
+
+    #[test]
+    fn should_report_line_of_invalid_utf8() {
+        let error = decode(b"12\n34\n5\xff6".to_vec(), None).unwrap_err();
+        assert!(error.to_string().starts_with("<input>:3: failed to read: "))
+    }
 }
@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::diagnostics::Mode;
+use crate::error::Error;
+use crate::file_util::read_text;
+use crate::registry::Entry;
+use crate::solution::Part;
+use crate::verify::Outcome;
+
+pub const EXAMPLES_DIR: &str = "examples";
+
+const ANSWER_FILES: [(Part, &str); 2] = [(Part::One, "part1"), (Part::Two, "part2")];
+
+/**
+* A worked example in `examples/day_NN` or `examples/day_NN_<label>`, holding an `input` file
+* and the expected answers in `part1` and `part2`. A part without an answer file is not run.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct Fixture {
+    pub day: u8,
+    pub dir: PathBuf,
+    pub expected: Vec<(Part, String)>
+}
+
+/**
+* The day a fixture directory belongs to: two digits after `day_`, then nothing or a `_` label.
+*/
+pub fn fixture_day(name: &str) -> Option<u8> {
+    let rest = name.strip_prefix("day_")?;
+    let (digits, label) = (rest.get(..2)?, &rest[2..]);
+    if !digits.bytes().all(|it| it.is_ascii_digit()) || !(label.is_empty() || label.starts_with('_')) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/**
+* Every fixture directory under `dir`, sorted by name. Other entries are ignored.
+*/
+pub fn discover(dir: &Path) -> Result<Vec<Fixture>, Error> {
+    let read_error = |source| Error::io(Some(dir), None, source);
+    let mut fixtures = Vec::new();
+    for entry in fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        let day = match path.file_name().and_then(|it| it.to_str()).and_then(fixture_day) {
+            Some(day) if path.is_dir() => day,
+            _ => continue
+        };
+        let mut expected = Vec::new();
+        for (part, file) in ANSWER_FILES.iter() {
+            let answer = path.join(file);
+            if answer.exists() {
+                expected.push((*part, String::from(read_text(&answer)?.trim())));
+            }
+        }
+        fixtures.push(Fixture { day, dir: path, expected });
+    }
+    fixtures.sort_by(|fixture, other| fixture.dir.cmp(&other.dir));
+    Ok(fixtures)
+}
+
+impl Fixture {
+    /**
+    * Solves the parts with an expected answer, rejecting any malformed line in the input. Answers
+    * for parts the day has not implemented are skipped, so they can be recorded ahead of time.
+    */
+    pub fn check(&self, entry: &Entry) -> Result<Vec<(Part, Outcome)>, Error> {
+        let input = self.dir.join("input");
+        let text = read_text(&input)?;
+        let expected = self.expected.iter()
+            .filter(|(part, _)| entry.solution.parts().contains(part))
+            .collect::<Vec<&(Part, String)>>();
+        let parts = expected.iter().map(|(part, _)| *part).collect::<Vec<Part>>();
+        let solved = entry.solution.solve(&text, &parts, Mode::Strict)
+            .map_err(|error| error.with_path(&input))?;
+        Ok(
+            expected.into_iter()
+                .map(|(part, expected)| {
+                    let actual = solved.answers.iter()
+                        .find(|answer| answer.part == *part)
+                        .and_then(|answer| answer.value.clone());
+                    let outcome = if actual.as_deref() == Some(expected.as_str()) {
+                        Outcome::Pass
+                    } else {
+                        Outcome::Fail { expected: expected.clone(), actual }
+                    };
+                    (*part, outcome)
+                })
+                .collect()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::*;
+    use crate::registry::{self, DAYS};
+
+    fn examples() -> Vec<Fixture> {
+        discover(&Path::new(env!("CARGO_MANIFEST_DIR")).join(EXAMPLES_DIR)).unwrap()
+    }
+
+    #[test]
+    fn should_read_day_from_fixture_name() {
+        assert_eq!(fixture_day("day_07"), Some(7));
+        assert_eq!(fixture_day("day_07_nested"), Some(7));
+        assert_eq!(fixture_day("day_7"), None);
+        assert_eq!(fixture_day("day_071"), None);
+        assert_eq!(fixture_day("README"), None)
+    }
+
+    #[test]
+    fn should_have_an_example_for_every_day() {
+        let fixtures = examples();
+        let missing = DAYS.iter()
+            .filter(|entry| !fixtures.iter().any(|fixture| fixture.day == entry.day))
+            .map(|entry| entry.day)
+            .collect::<Vec<u8>>();
+        assert_eq!(missing, Vec::<u8>::new())
+    }
+
+    #[test]
+    fn should_solve_every_example() {
+        let mut failures = Vec::new();
+        for fixture in examples() {
+            let entry = match registry::get(fixture.day) {
+                Some(entry) => entry,
+                None => {
+                    failures.push(format!("{}: day {} is not implemented", fixture.dir.display(), fixture.day));
+                    continue;
+                }
+            };
+            match fixture.check(entry) {
+                Ok(outcomes) => failures.extend(
+                    outcomes.into_iter()
+                        .filter(|(_, outcome)| *outcome != Outcome::Pass)
+                        .map(|(part, outcome)| format!("{} part {}: {}", fixture.dir.display(), part, outcome))
+                ),
+                Err(error) => failures.push(error.to_string())
+            }
+        }
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"))
+    }
+}
@@ -0,0 +1,648 @@
+use std::collections::HashSet;
+use itertools::Itertools;
+use crate::day_eleven::{get_adjacent_seats, get_non_floor_adjacent_seats, revise_seats, Seat};
+use crate::grid::{Grid, Point};
+use crate::random::Random;
+use crate::registry;
+
+/**
+* Writes a random puzzle input of roughly `size` records. Each day clamps `size` to what its
+* solver can answer without overflowing or running for too long.
+*/
+pub type Generator = fn(&mut Random, usize) -> String;
+
+pub const DEFAULT_SIZE: usize = 100;
+
+/**
+* The input the given day's generator writes for `seed`, or `None` if the day is not
+* implemented. The same seed and size always give the same input.
+*/
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    registry::get(day).map(|entry| (entry.generator)(&mut Random::new(seed), size))
+}
+
+fn lines(lines: impl Iterator<Item = String>) -> String {
+    lines.map(|line| line + "\n").collect()
+}
+
+fn grid(random: &mut Random, width: usize, height: usize, pixels: &[char]) -> String {
+    lines((0..height).map(|_| (0..width).map(|_| *random.choose(pixels)).collect()))
+}
+
+fn completes_sum(numbers: &[usize], number: usize) -> bool {
+    numbers.iter().enumerate().any(|(index, first)| {
+        first + number == 2020 || numbers[index + 1..].iter().any(|second| first + second + number == 2020)
+    })
+}
+
+/**
+* Expense reports with exactly one pair and one triple that add up to 2020, among up to 200
+* entries.
+*/
+pub fn day_one(random: &mut Random, size: usize) -> String {
+    let mut numbers = loop {
+        let pair = random.range(1, 2019);
+        let first = random.range(1, 2018);
+        let second = random.range(1, 2019 - first);
+        let planted = vec!(pair, 2020 - pair, first, second, 2020 - first - second);
+        let pairs = planted.iter().tuple_combinations().filter(|(a, b)| *a + *b == 2020).count();
+        let triples = planted.iter().tuple_combinations().filter(|(a, b, c)| *a + *b + *c == 2020).count();
+        if pairs == 1 && triples == 1 {
+            break planted;
+        }
+    };
+    while numbers.len() < size.clamp(5, 200) {
+        let number = random.range(1, 2019);
+        if !completes_sum(&numbers, number) {
+            numbers.push(number);
+        }
+    }
+    random.shuffle(&mut numbers);
+    lines(numbers.into_iter().map(|number| number.to_string()))
+}
+
+/**
+* Password policies whose second position is never before the first, and sometimes the same,
+* and always within the password.
+*/
+pub fn day_two(random: &mut Random, size: usize) -> String {
+    lines((0..size.max(1)).map(|_| {
+        let letter = (b'a' + random.below(26) as u8) as char;
+        let low = random.range(1, 8);
+        let high = random.range(low, low + 10);
+        let password = (0..random.range(high, high + 6))
+            .map(|_| if random.chance(3) { letter } else { (b'a' + random.below(26) as u8) as char })
+            .collect::<String>();
+        format!("{}-{} {}: {}", low, high, letter, password)
+    }))
+}
+
+/**
+* A slope 31 squares wide with a tree on about a quarter of them.
+*/
+pub fn day_three(random: &mut Random, size: usize) -> String {
+    grid(random, 31, size.max(1), &['#', '.', '.', '.'])
+}
+
+const EYE_COLOURS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+fn passport_field(random: &mut Random, field: &str, valid: bool) -> String {
+    let digits = |random: &mut Random, count: usize| (0..count)
+        .map(|_| (b'0' + random.below(10) as u8) as char)
+        .collect::<String>();
+    let value = match (field, valid) {
+        ("byr", true) => random.range(1920, 2002).to_string(),
+        ("iyr", true) => random.range(2010, 2020).to_string(),
+        ("eyr", true) => random.range(2020, 2030).to_string(),
+        ("byr", false) | ("iyr", false) | ("eyr", false) => random.range(1900, 2040).to_string(),
+        ("hgt", true) => match random.chance(2) {
+            true => format!("{}cm", random.range(150, 193)),
+            false => format!("{}in", random.range(59, 76))
+        },
+        ("hgt", false) => random.range(50, 200).to_string(),
+        ("hcl", true) => format!("#{:06x}", random.below(1 << 24)),
+        ("hcl", false) => format!("{:06x}", random.below(1 << 24)),
+        ("ecl", true) => String::from(*random.choose(&EYE_COLOURS)),
+        ("ecl", false) => String::from(*random.choose(&["xry", "red", "zzz"])),
+        ("pid", true) => digits(random, 9),
+        ("pid", false) => {
+            let count = *random.choose(&[8, 10]);
+            digits(random, count)
+        },
+        _ => random.range(100, 999).to_string()
+    };
+    format!("{}:{}", field, value)
+}
+
+/**
+* Passports that sometimes miss a field or hold a value out of range, spread over a few lines.
+*/
+pub fn day_four(random: &mut Random, size: usize) -> String {
+    let passports = (0..size.max(1)).map(|_| {
+        let mut fields = Vec::new();
+        for field in ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"].iter() {
+            if !random.chance(if *field == "cid" { 2 } else { 10 }) {
+                let valid = !random.chance(10);
+                fields.push(passport_field(random, field, valid));
+            }
+        }
+        if fields.is_empty() {
+            fields.push(passport_field(random, "cid", true));
+        }
+        random.shuffle(&mut fields);
+        let mut passport = String::new();
+        for (index, field) in fields.iter().enumerate() {
+            if index > 0 {
+                passport.push(if random.chance(4) { '\n' } else { ' ' });
+            }
+            passport.push_str(field);
+        }
+        passport + "\n"
+    }).collect::<Vec<String>>();
+    passports.join("\n")
+}
+
+/**
+* Boarding passes for a run of seats with one missing from the middle.
+*/
+pub fn day_five(random: &mut Random, size: usize) -> String {
+    let count = size.clamp(3, 900);
+    let start = random.range(8, 1016 - count);
+    let missing = random.range(start + 1, start + count - 1);
+    let mut seats = (start..=start + count)
+        .filter(|seat| *seat != missing)
+        .collect::<Vec<usize>>();
+    random.shuffle(&mut seats);
+    lines(seats.into_iter().map(|seat| {
+        let row = (0..7).rev().map(|bit| if (seat >> (3 + bit)) & 1 == 1 { 'B' } else { 'F' });
+        let column = (0..3).rev().map(|bit| if (seat >> bit) & 1 == 1 { 'R' } else { 'L' });
+        row.chain(column).collect()
+    }))
+}
+
+/**
+* Groups of one to five people, each answering at least one question.
+*/
+pub fn day_six(random: &mut Random, size: usize) -> String {
+    let groups = (0..size.max(1)).map(|_| {
+        lines((0..random.range(1, 5)).map(|_| {
+            let mut questions = ('a'..='z').filter(|_| random.chance(3)).collect::<Vec<char>>();
+            if questions.is_empty() {
+                questions.push((b'a' + random.below(26) as u8) as char);
+            }
+            random.shuffle(&mut questions);
+            questions.into_iter().collect()
+        }))
+    }).collect::<Vec<String>>();
+    groups.join("\n")
+}
+
+const ADJECTIVES: [&str; 16] = [
+    "light", "dark", "bright", "muted", "shiny", "faded", "dotted", "vibrant",
+    "pale", "dim", "clear", "drab", "plaid", "posh", "striped", "wavy"
+];
+const COLOURS: [&str; 16] = [
+    "red", "orange", "white", "yellow", "gold", "olive", "plum", "blue",
+    "black", "green", "teal", "tan", "cyan", "lime", "coral", "indigo"
+];
+
+/**
+* Bag rules without cycles, where shiny gold is held by some bag and holds some bags.
+*/
+pub fn day_seven(random: &mut Random, size: usize) -> String {
+    let depth = 6;
+    let mut names = ADJECTIVES.iter()
+        .flat_map(|adjective| COLOURS.iter().map(move |colour| format!("{} {}", adjective, colour)))
+        .filter(|name| name != "shiny gold")
+        .collect::<Vec<String>>();
+    random.shuffle(&mut names);
+    names.truncate(size.clamp(depth + 1, names.len()) - 1);
+    names.insert(depth / 2, String::from("shiny gold"));
+    // A bag only holds bags from deeper levels, so the rules cannot loop.
+    let level = |index: usize| index % (depth + 1);
+    let mut children = (0..names.len())
+        .map(|index| {
+            let deeper = (0..names.len()).filter(|other| level(*other) > level(index)).collect::<Vec<usize>>();
+            if deeper.is_empty() || random.chance(4) {
+                return Vec::new();
+            }
+            let mut held = (0..random.range(1, 4)).map(|_| *random.choose(&deeper)).collect::<Vec<usize>>();
+            held.sort_unstable();
+            held.dedup();
+            held
+        })
+        .collect::<Vec<Vec<usize>>>();
+    let gold = depth / 2;
+    if children[gold].is_empty() {
+        children[gold].push(gold + 1);
+    }
+    let holder = random.below(gold);
+    if !children[holder].contains(&gold) {
+        children[holder].push(gold);
+    }
+    let mut rules = names.iter().zip(children).map(|(name, held)| {
+        if held.is_empty() {
+            return format!("{} bags contain no other bags.", name);
+        }
+        let contents = held.iter()
+            .map(|child| {
+                let count = random.range(1, 5);
+                format!("{} {} bag{}", count, names[*child], if count == 1 { "" } else { "s" })
+            })
+            .collect::<Vec<String>>();
+        format!("{} bags contain {}.", name, contents.join(", "))
+    }).collect::<Vec<String>>();
+    random.shuffle(&mut rules);
+    lines(rules.into_iter())
+}
+
+/**
+* A program that loops because of one `jmp`, which as a `nop` would let it terminate. Every
+* jump stays within the program or lands just past its end.
+*/
+pub fn day_eight(random: &mut Random, size: usize) -> String {
+    let length = size.clamp(5, 1000) as isize;
+    let mut program = (0..length)
+        .map(|index| match random.below(5) {
+            0 | 1 => (String::from("acc"), random.range(1, 100) as isize - 50),
+            2 | 3 => (String::from("jmp"), random.range(1, (length - index).min(8) as usize) as isize),
+            _ => (String::from("nop"), random.range(0, length as usize) as isize - index)
+        })
+        .collect::<Vec<(String, isize)>>();
+    // Only forward jumps so far, so the program terminates. Jumping back from a visited
+    // instruction to an earlier one makes it loop.
+    let mut visited = Vec::new();
+    let mut current = 0;
+    while current < length {
+        visited.push(current);
+        current += match program[current as usize].0.as_str() {
+            "jmp" => program[current as usize].1,
+            _ => 1
+        };
+    }
+    if visited.len() < 2 {
+        program[0] = (String::from("nop"), 0);
+        visited = vec!(0, 1);
+    }
+    let from = random.range(1, visited.len() - 1);
+    let to = visited[random.below(from)];
+    program[visited[from] as usize] = (String::from("jmp"), to - visited[from]);
+    lines(program.into_iter().map(|(operation, argument)| format!("{} {:+}", operation, argument)))
+}
+
+fn is_sum_of_two(value: u64, numbers: &[u64]) -> bool {
+    numbers.iter().enumerate().any(|(index, first)| {
+        numbers[index + 1..].iter().any(|second| first + second == value)
+    })
+}
+
+/**
+* A preamble of 25 numbers, then numbers that are each the sum of two of the 25 before them,
+* except one that is instead the sum of a contiguous run of earlier numbers.
+*/
+pub fn day_nine(random: &mut Random, size: usize) -> String {
+    let length = 25 + size.clamp(10, 1000);
+    let mut numbers = (1..=60).collect::<Vec<u64>>();
+    random.shuffle(&mut numbers);
+    numbers.truncate(25);
+    let weakness = random.range(25 + (length - 25) / 2, length - 1);
+    while numbers.len() < length {
+        let window = &numbers[numbers.len() - 25..];
+        let next = if numbers.len() == weakness {
+            (0..100)
+                .map(|_| {
+                    let start = random.below(numbers.len() - 2);
+                    let end = random.range(start + 2, (start + 5).min(numbers.len()));
+                    numbers[start..end].iter().sum::<u64>()
+                })
+                .find(|sum| !is_sum_of_two(*sum, window))
+                .unwrap_or_else(|| window.iter().sum::<u64>() * 2 + 1)
+        } else {
+            // Adding the smaller numbers keeps the values growing slowly.
+            let mut smallest = window.to_vec();
+            smallest.sort_unstable();
+            smallest.dedup();
+            let first = random.below(smallest.len().min(5) - 1);
+            let second = random.range(first + 1, (first + 4).min(smallest.len() - 1));
+            smallest[first] + smallest[second]
+        };
+        numbers.push(next);
+    }
+    lines(numbers.into_iter().map(|number| number.to_string()))
+}
+
+/**
+* Adapters one or three jolts apart, with at most four in a row one jolt apart as in the real
+* puzzle, which keeps the number of arrangements in range.
+*/
+pub fn day_ten(random: &mut Random, size: usize) -> String {
+    let mut adapters = Vec::new();
+    let (mut joltage, mut run) = (0, 0);
+    for _ in 0..size.clamp(1, 100) {
+        let gap = if run == 4 || random.chance(3) { 3 } else { 1 };
+        run = if gap == 1 { run + 1 } else { 0 };
+        joltage += gap;
+        adapters.push(joltage);
+    }
+    random.shuffle(&mut adapters);
+    lines(adapters.into_iter().map(|adapter| adapter.to_string()))
+}
+
+fn settles(seats: &Grid<Seat>, max_taken: usize, resolver: fn(Point, &Grid<Seat>) -> [Option<&Seat>; 8]) -> bool {
+    let mut seats = seats.clone();
+    (0..1000).any(|_| !revise_seats(max_taken, &mut seats, resolver))
+}
+
+/**
+* A square seat layout, with floor in about one place in five. Some layouts flip back and forth
+* for ever, so those are drawn again until one settles under both rules.
+*/
+pub fn day_eleven(random: &mut Random, size: usize) -> String {
+    let side = size.clamp(1, 100);
+    loop {
+        let cells = (0..side * side)
+            .map(|_| if random.chance(5) { Seat::FLOOR } else { Seat::EMPTY })
+            .collect::<Vec<Seat>>();
+        let seats = Grid::from_cells(side, cells).expect("the cells fill the square");
+        if settles(&seats, 3, get_adjacent_seats) && settles(&seats, 4, get_non_floor_adjacent_seats) {
+            return lines(seats.rows().map(|row| row.iter().map(|seat| seat.to_string()).collect()));
+        }
+    }
+}
+
+/**
+* Navigation instructions, turning by whole quarter turns.
+*/
+pub fn day_twelve(random: &mut Random, size: usize) -> String {
+    lines((0..size.max(1)).map(|_| {
+        let action = *random.choose(&['N', 'E', 'S', 'W', 'L', 'R', 'F', 'F']);
+        let amount = match action {
+            'L' | 'R' => *random.choose(&[90, 180, 270]),
+            _ => random.range(1, 100)
+        };
+        format!("{}{}", action, amount)
+    }))
+}
+
+const BUS_PRIMES: [usize; 15] = [7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61];
+
+/**
+* A departure time and up to eight buses, all with distinct prime ids so the timetable has a
+* solution.
+*/
+pub fn day_thirteen(random: &mut Random, size: usize) -> String {
+    let mut buses = BUS_PRIMES.to_vec();
+    random.shuffle(&mut buses);
+    let mut slots = Vec::new();
+    for bus in buses.into_iter().take(size.clamp(1, 8)) {
+        if !slots.is_empty() {
+            slots.extend((0..random.below(6)).map(|_| String::from("x")));
+        }
+        slots.push(bus.to_string());
+    }
+    format!("{}\n{}\n", random.range(100_000, 1_000_000), slots.join(","))
+}
+
+/**
+* Masks with at most nine floating bits, each followed by a few writes.
+*/
+pub fn day_fourteen(random: &mut Random, size: usize) -> String {
+    let mut program = Vec::new();
+    for _ in 0..size.max(1) {
+        let mut mask = (0..36).map(|_| *random.choose(&['0', '1'])).collect::<Vec<char>>();
+        for _ in 0..random.range(0, 9) {
+            mask[random.below(36)] = 'X';
+        }
+        program.push(format!("mask = {}", mask.into_iter().collect::<String>()));
+        for _ in 0..random.range(1, 4) {
+            program.push(format!("mem[{}] = {}", random.below(1 << 16), random.next_u64() >> 28));
+        }
+    }
+    lines(program.into_iter())
+}
+
+/**
+* Up to ten distinct starting numbers.
+*/
+pub fn day_fifteen(random: &mut Random, size: usize) -> String {
+    let mut numbers = (0..20).collect::<Vec<usize>>();
+    random.shuffle(&mut numbers);
+    let starting = numbers.iter().take(size.clamp(1, 10)).map(|number| number.to_string()).collect::<Vec<String>>();
+    format!("{}\n", starting.join(","))
+}
+
+const FIELD_NAMES: [&str; 20] = [
+    "location", "station", "platform", "track", "date", "time", "arrival", "class", "duration",
+    "price", "route", "row", "seat", "train", "type", "wagon", "zone", "carriage", "gate", "coach"
+];
+
+/**
+* Ticket notes whose columns can be told apart by elimination: the values in each column sit
+* in their own block, and each rule accepts its own block and every block above it. About one
+* nearby ticket in four holds a value no rule accepts. The first six fields are departures.
+*/
+pub fn day_sixteen(random: &mut Random, size: usize) -> String {
+    let fields = size.clamp(6, 20);
+    let block = |rank: usize| 50 + 10 * rank;
+    let mut ranks = (0..fields).collect::<Vec<usize>>();
+    random.shuffle(&mut ranks);
+    let mut columns = (0..fields).collect::<Vec<usize>>();
+    random.shuffle(&mut columns);
+    let mut notes = ranks.iter().enumerate().map(|(field, rank)| {
+        let name = if field < 6 { format!("departure {}", FIELD_NAMES[field]) } else { String::from(FIELD_NAMES[field]) };
+        let extra = block(fields + rank);
+        format!("{}: {}-{} or {}-{}", name, block(*rank), block(fields) - 1, extra, extra + 5)
+    }).collect::<Vec<String>>();
+    let ticket = |random: &mut Random, valid: bool| {
+        let mut values = columns.iter()
+            .map(|field| block(ranks[*field]) + random.below(10))
+            .collect::<Vec<usize>>();
+        if !valid {
+            values[random.below(fields)] = random.below(50);
+        }
+        values.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(",")
+    };
+    notes.push(String::new());
+    notes.push(String::from("your ticket:"));
+    notes.push(ticket(random, true));
+    notes.push(String::new());
+    notes.push(String::from("nearby tickets:"));
+    for index in 0..size.max(1) {
+        let valid = index == 0 || !random.chance(4);
+        notes.push(ticket(random, valid));
+    }
+    lines(notes.into_iter())
+}
+
+/**
+* An initial slice of up to eight by eight cubes.
+*/
+pub fn day_seventeen(random: &mut Random, size: usize) -> String {
+    let side = size.clamp(1, 8);
+    grid(random, side, side, &['#', '.'])
+}
+
+fn expression(random: &mut Random, depth: usize) -> String {
+    let terms = (0..random.range(2, if depth == 0 { 5 } else { 3 }))
+        .map(|_| if depth < 2 && random.chance(4) {
+            format!("({})", expression(random, depth + 1))
+        } else {
+            random.range(1, 9).to_string()
+        })
+        .collect::<Vec<String>>();
+    let mut expression = terms[0].clone();
+    for term in terms[1..].iter() {
+        expression.push_str(if random.chance(2) { " + " } else { " * " });
+        expression.push_str(term);
+    }
+    expression
+}
+
+/**
+* Expressions of at most fifteen single digit numbers, so no evaluation order overflows.
+*/
+pub fn day_eighteen(random: &mut Random, size: usize) -> String {
+    lines((0..size.max(1)).map(|_| loop {
+        let expression = expression(random, 0);
+        if expression.chars().filter(char::is_ascii_digit).count() <= 15 {
+            break expression;
+        }
+    }))
+}
+
+fn derive(random: &mut Random, rules: &[Vec<Vec<usize>>], rule: usize, sentence: &mut String) {
+    match rules[rule].as_slice() {
+        [] => sentence.push(if rule == 0 { 'a' } else { 'b' }),
+        alternatives => for part in random.choose(alternatives).clone() {
+            derive(random, rules, part, sentence)
+        }
+    }
+}
+
+/**
+* A grammar built in layers over the rules for `a` and `b`, so it has no loops and expands to a
+* short regular expression, followed by messages of which about half match it.
+*/
+pub fn day_nineteen(random: &mut Random, size: usize) -> String {
+    // Rules 0 and 1 are the terminals, the last rule is the start.
+    let mut rules: Vec<Vec<Vec<usize>>> = vec!(Vec::new(), Vec::new());
+    let mut previous = vec!(0, 1);
+    for _ in 0..random.range(2, 4) {
+        let layer = (0..2).map(|_| {
+            let alternatives = (0..2)
+                .map(|_| vec!(*random.choose(&previous), *random.choose(&previous)))
+                .collect();
+            rules.push(alternatives);
+            rules.len() - 1
+        }).collect();
+        previous = layer;
+    }
+    rules.push(vec!((0..3).map(|_| *random.choose(&previous)).collect()));
+    let start = rules.len() - 1;
+    let mut numbers = (1..rules.len() + 10).collect::<Vec<usize>>();
+    random.shuffle(&mut numbers);
+    numbers[start] = 0;
+    let mut grammar = rules.iter().enumerate().map(|(rule, alternatives)| {
+        let productions = match rule {
+            0 => String::from("\"a\""),
+            1 => String::from("\"b\""),
+            _ => alternatives.iter()
+                .map(|parts| parts.iter().map(|part| numbers[*part].to_string()).collect::<Vec<String>>().join(" "))
+                .collect::<Vec<String>>()
+                .join(" | ")
+        };
+        format!("{}: {}", numbers[rule], productions)
+    }).collect::<Vec<String>>();
+    random.shuffle(&mut grammar);
+    let messages = (0..size.max(1)).map(|_| {
+        let mut message = String::new();
+        derive(random, &rules, start, &mut message);
+        if random.chance(2) {
+            message = message.chars().map(|_| *random.choose(&['a', 'b'])).collect();
+        }
+        message
+    });
+    format!("{}\n{}", lines(grammar.into_iter()), lines(messages))
+}
+
+type Tile = [[bool; 10]; 10];
+
+fn transform(random: &mut Random, tile: Tile) -> Tile {
+    let mut tile = tile;
+    for _ in 0..random.below(4) {
+        let mut rotated = [[false; 10]; 10];
+        for (row, pixels) in rotated.iter_mut().enumerate() {
+            for (column, pixel) in pixels.iter_mut().enumerate() {
+                *pixel = tile[9 - column][row];
+            }
+        }
+        tile = rotated;
+    }
+    if random.chance(2) {
+        tile.iter_mut().for_each(|row| row.reverse());
+    }
+    tile
+}
+
+/**
+* A square jigsaw of two by two up to eight by eight tiles, cut from one image so neighbouring
+* tiles share an edge. No other pair of edges match, even flipped, so exactly four tiles are
+* corners. Each tile is rotated or flipped at random.
+*/
+pub fn day_twenty(random: &mut Random, size: usize) -> String {
+    let tiles = size.clamp(2, 8);
+    let side = 9 * tiles + 1;
+    let mut image = (0..side)
+        .map(|_| (0..side).map(|_| random.chance(2)).collect())
+        .collect::<Vec<Vec<bool>>>();
+    let mut edges = HashSet::new();
+    // Each edge keeps its corner pixels, which other edges share, and redraws the rest until
+    // it differs from every edge so far either way round.
+    for line in 0..=tiles {
+        for across in 0..tiles {
+            for vertical in [false, true].iter() {
+                let pixel = |offset: usize| if *vertical { (9 * across + offset, 9 * line) } else { (9 * line, 9 * across + offset) };
+                loop {
+                    for offset in 1..9 {
+                        let (row, column) = pixel(offset);
+                        image[row][column] = random.chance(2);
+                    }
+                    let edge = (0..10).map(|offset| {
+                        let (row, column) = pixel(offset);
+                        image[row][column]
+                    }).collect::<Vec<bool>>();
+                    let reversed = edge.iter().rev().copied().collect::<Vec<bool>>();
+                    if !edges.contains(&edge) && !edges.contains(&reversed) {
+                        edges.insert(edge);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    let mut ids = (1000..10000).collect::<Vec<usize>>();
+    random.shuffle(&mut ids);
+    let mut pieces = Vec::new();
+    for (index, id) in ids.into_iter().take(tiles * tiles).enumerate() {
+        let (top, left) = (9 * (index / tiles), 9 * (index % tiles));
+        let mut tile = [[false; 10]; 10];
+        for (row, pixels) in tile.iter_mut().enumerate() {
+            pixels.copy_from_slice(&image[top + row][left..left + 10]);
+        }
+        let rows = transform(random, tile).iter()
+            .map(|row| row.iter().map(|pixel| if *pixel { '#' } else { '.' }).collect::<String>())
+            .collect::<Vec<String>>();
+        pieces.push(format!("Tile {}:\n{}\n", id, rows.join("\n")));
+    }
+    random.shuffle(&mut pieces);
+    pieces.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate::*;
+    use crate::diagnostics::Mode;
+    use crate::registry::DAYS;
+
+    #[test]
+    fn should_generate_inputs_every_day_parses() {
+        for entry in DAYS.iter() {
+            for seed in 0..5 {
+                let text = generate(entry.day, seed, 20).unwrap();
+                assert!(
+                    entry.solution.solve(&text, &[], Mode::Strict).is_ok(),
+                    "day {} seed {} rejected:\n{}", entry.day, seed, text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_repeat_for_the_same_seed() {
+        for entry in DAYS.iter() {
+            assert_eq!(generate(entry.day, 7, 10), generate(entry.day, 7, 10));
+        }
+        assert_ne!(generate(1, 1, 10), generate(1, 2, 10));
+        assert_eq!(generate(21, 1, 10), None)
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+use std::iter::successors;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+
+/**
+* A column and row, counting from the top left. Signed so that neighbours of the edges can be
+* described and rejected by the lookups rather than overflowing.
+*/
+pub type Point = (isize, isize);
+
+pub const ORTHOGONAL: [Point; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+pub const ADJACENT: [Point; 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1, 0), (1, 0),
+    (-1, 1), (0, 1), (1, 1)
+];
+
+/**
+* A rectangular grid stored row by row.
+*/
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>
+}
+
+/**
+* Reads one row of a character map. The first row read sets `width`, which every later row must
+* match.
+*/
+fn read_row<T>(
+    number: usize,
+    line: &str,
+    width: &mut Option<usize>,
+    cell: impl Fn(char) -> Option<T>
+) -> Result<Vec<T>, Diagnostic> {
+    let row_width = line.chars().count();
+    if let Some(expected) = *width {
+        if expected != row_width {
+            return Err(Diagnostic::new(
+                number,
+                line,
+                format!("expected {} columns, found {}", expected, row_width)
+            ));
+        }
+    }
+    let row = line.char_indices()
+        .map(|(offset, character)| cell(character).ok_or_else(|| Diagnostic::at(
+            number,
+            line,
+            &line[offset..offset + character.len_utf8()],
+            format!("unexpected '{}' in map", character)
+        )))
+        .collect::<Result<Vec<T>, Diagnostic>>()?;
+    width.get_or_insert(row_width);
+    Ok(row)
+}
+
+impl<T> Grid<T> {
+    /**
+    * Builds a grid from cells in row order, or `None` if they do not fill whole rows.
+    */
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Option<Self> {
+        match (width, cells.len()) {
+            (0, 0) => Some(Grid { width, height: 0, cells }),
+            (0, _) => None,
+            (_, length) if length % width != 0 => None,
+            (_, length) => Some(Grid { width, height: length / width, cells })
+        }
+    }
+
+    /**
+    * Parses a character map, one row per line. Every row must be as wide as the first, and
+    * `cell` returns `None` for characters that do not belong on the map.
+    */
+    pub fn parse<'a>(
+        lines: impl Iterator<Item = (usize, &'a str)>,
+        cell: impl Fn(char) -> Option<T>
+    ) -> Result<Self, Diagnostic> {
+        let mut width = None;
+        let mut cells = Vec::new();
+        for (number, line) in lines {
+            cells.extend(read_row(number, line, &mut width, &cell)?);
+        }
+        Ok(Grid::from_rows(width, cells))
+    }
+
+    /**
+    * Like `parse`, but a row that is the wrong width or holds a character that does not belong
+    * on the map is recorded and left out, and the rows either side of it become neighbours.
+    */
+    pub fn parse_skipping<'a>(
+        lines: impl Iterator<Item = (usize, &'a str)>,
+        cell: impl Fn(char) -> Option<T>,
+        diagnostics: &mut Diagnostics
+    ) -> Self {
+        let mut width = None;
+        let mut cells = Vec::new();
+        for (number, line) in lines {
+            if let Some(row) = diagnostics.check(read_row(number, line, &mut width, &cell)) {
+                cells.extend(row);
+            }
+        }
+        Grid::from_rows(width, cells)
+    }
+
+    fn from_rows(width: Option<usize>, cells: Vec<T>) -> Self {
+        let width = width.unwrap_or_default();
+        let height = cells.len().checked_div(width).unwrap_or_default();
+        Grid { width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    fn index(&self, (x, y): Point) -> Option<usize> {
+        if self.contains((x, y)) {
+            Some(y as usize * self.width + x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index(point).map(move |index| &mut self.cells[index])
+    }
+
+    /**
+    * Looks up a point as if the grid repeated forever in every direction.
+    */
+    pub fn get_wrapping(&self, (x, y): Point) -> Option<&T> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        self.get((x.rem_euclid(self.width as isize), y.rem_euclid(self.height as isize)))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        self.cells.get(y * self.width..(y + 1) * self.width).filter(|_| y < self.height)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).filter_map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let height = if x < self.width { self.height } else { 0 };
+        (0..height).map(move |y| &self.cells[y * self.width + x])
+    }
+
+    /**
+    * Every point and its cell, row by row.
+    */
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        let width = self.width.max(1);
+        self.cells.iter()
+            .enumerate()
+            .map(move |(index, cell)| (((index % width) as isize, (index / width) as isize), cell))
+    }
+
+    /**
+    * The cells one step from `point` in each of `directions` that are on the grid.
+    */
+    pub fn neighbours_in<'a>(&'a self, (x, y): Point, directions: &'a [Point]) -> impl Iterator<Item = (Point, &'a T)> {
+        directions.iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter_map(move |point| self.get(point).map(|cell| (point, cell)))
+    }
+
+    /**
+    * The up to eight cells touching `point`, including diagonals.
+    */
+    pub fn neighbours(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours_in(point, &ADJACENT)
+    }
+
+    /**
+    * The up to four cells sharing an edge with `point`.
+    */
+    pub fn orthogonal_neighbours(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours_in(point, &ORTHOGONAL)
+    }
+
+    /**
+    * The cells seen looking from `start` in `direction`, nearest first, stopping at the edge.
+    * `start` itself is not included.
+    */
+    pub fn ray(&self, start: Point, (dx, dy): Point) -> impl Iterator<Item = (Point, &T)> {
+        successors(Some(start), move |(x, y)| Some((x + dx, y + dy)))
+            .skip(1)
+            .take_while(move |_| (dx, dy) != (0, 0))
+            .map_while(move |point| self.get(point).map(|cell| (point, cell)))
+    }
+
+    pub fn map<U>(&self, convert: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(convert).collect() }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::*;
+
+    fn digits(lines: &[&str]) -> Result<Grid<u32>, Diagnostic> {
+        Grid::parse(
+            lines.iter().enumerate().map(|(index, line)| (index + 1, *line)),
+            |character| character.to_digit(10)
+        )
+    }
+
+    #[test]
+    fn should_parse_and_render_map() {
+        let grid = digits(&["123", "456"]).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get((2, 1)), Some(&6));
+        assert_eq!(grid.column(1).collect::<Vec<&u32>>(), vec!(&2, &5));
+        assert_eq!(grid.row(1), Some(&[4, 5, 6][..]));
+        assert_eq!(grid.to_string(), "123\n456")
+    }
+
+    #[test]
+    fn should_reject_ragged_or_unknown_map() {
+        assert_eq!(digits(&["123", "45"]).map_err(|it| it.line), Err(2));
+        assert_eq!(digits(&["123", "4x6"]).map_err(|it| (it.line, it.column)), Err((2, 2)))
+    }
+
+    #[test]
+    fn should_check_and_wrap_coordinates() {
+        let grid = digits(&["123", "456"]).unwrap();
+        assert_eq!(grid.get((-1, 0)), None);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get_wrapping((-1, 0)), Some(&3));
+        assert_eq!(grid.get_wrapping((4, 3)), Some(&5))
+    }
+
+    #[test]
+    fn should_find_neighbours_and_cast_rays() {
+        let grid = digits(&["123", "456", "789"]).unwrap();
+        assert_eq!(grid.neighbours((0, 0)).map(|(_, cell)| *cell).collect::<Vec<u32>>(), vec!(2, 4, 5));
+        assert_eq!(grid.neighbours((1, 1)).count(), 8);
+        assert_eq!(grid.orthogonal_neighbours((1, 1)).map(|(_, cell)| *cell).collect::<Vec<u32>>(), vec!(2, 6, 8, 4));
+        assert_eq!(grid.ray((0, 0), (1, 1)).map(|(_, cell)| *cell).collect::<Vec<u32>>(), vec!(5, 9));
+        assert_eq!(grid.ray((0, 0), (0, 0)).count(), 0)
+    }
+}
@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+use std::env;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::embedded;
+use crate::error::Error;
+use crate::file_util::{read_stdin_text, read_text};
+
+pub const ASSETS_VARIABLE: &str = "ADVENT_ASSETS";
+const DEFAULT_ASSETS: &str = "assets";
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+    Embedded(&'static str)
+}
+
+impl InputSource {
+    /**
+    * `-` reads from stdin, anything else is treated as a path.
+    */
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(PathBuf::from(arg))
+        }
+    }
+
+    /**
+    * Embedded inputs are borrowed from the binary rather than copied.
+    */
+    pub fn read_text(&self) -> Result<Cow<'static, str>, Error> {
+        match self {
+            InputSource::File(path) => read_text(path).map(Cow::Owned),
+            InputSource::Stdin => read_stdin_text().map(Cow::Owned).map_err(|error| self.annotate(error)),
+            InputSource::Embedded(name) => embedded::get(name)
+                .map(Cow::Borrowed)
+                .ok_or_else(|| Error::io(
+                    Some(&self.path()),
+                    None,
+                    io::Error::new(io::ErrorKind::NotFound, "not embedded in this build")
+                ))
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.to_string())
+    }
+
+    /**
+    * Attaches this source's path to an error that was raised without one, so stdin and embedded
+    * inputs are named in errors as they are everywhere else.
+    */
+    pub fn annotate(&self, error: Error) -> Error {
+        match self {
+            InputSource::File(path) => error.with_path(path),
+            InputSource::Stdin | InputSource::Embedded(_) => error.with_path(&self.path())
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "<stdin>"),
+            InputSource::Embedded(name) => write!(f, "embedded:{}", name)
+        }
+    }
+}
+
+/**
+* Where the default inputs and answers come from.
+*/
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Assets {
+    Dir(PathBuf),
+    Embedded
+}
+
+impl Assets {
+    /**
+    * An explicit flag wins over `ADVENT_ASSETS`. Without either, a binary built with the
+    * `embed-assets` feature uses its own copy, and any other build reads `assets` in the working
+    * directory.
+    */
+    pub fn resolve(flag: Option<&Path>) -> Self {
+        match flag.map(Path::to_path_buf).or_else(|| env::var_os(ASSETS_VARIABLE).map(PathBuf::from)) {
+            Some(dir) => Assets::Dir(dir),
+            None if embedded::ENABLED => Assets::Embedded,
+            None => Assets::Dir(PathBuf::from(DEFAULT_ASSETS))
+        }
+    }
+
+    pub fn source(&self, name: &'static str) -> InputSource {
+        match self {
+            Assets::Dir(dir) => InputSource::File(dir.join(name)),
+            Assets::Embedded => InputSource::Embedded(name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::*;
+
+    #[test]
+    fn should_read_dash_as_stdin() {
+        assert_eq!(InputSource::from_arg("-"), InputSource::Stdin);
+        assert_eq!(
+            InputSource::from_arg("my/day_seven"),
+            InputSource::File(PathBuf::from("my/day_seven"))
+        )
+    }
+
+    #[test]
+    fn should_resolve_assets_from_flag() {
+        let assets = Assets::resolve(Some(Path::new("/tmp/inputs")));
+        assert_eq!(
+            assets.source("day_seven"),
+            InputSource::File(PathBuf::from("/tmp/inputs/day_seven"))
+        )
+    }
+
+    #[test]
+    fn should_read_embedded_input_only_when_built_in() {
+        let source = InputSource::Embedded("day_one");
+        assert_eq!(source.to_string(), "embedded:day_one");
+        assert_eq!(source.read_text().is_ok(), embedded::ENABLED);
+        let error = InputSource::Embedded("day_zero").read_text().unwrap_err();
+        assert!(error.to_string().starts_with("embedded:day_zero: failed to read: "))
+    }
+
+    #[test]
+    fn should_name_stdin_in_errors() {
+        let error = InputSource::Stdin.annotate(Error::io(None, None, io::Error::from(io::ErrorKind::InvalidData)));
+        assert!(error.to_string().starts_with("<stdin>: failed to read: "))
+    }
+}
@@ -0,0 +1,57 @@
+/*!
+* Solutions to Advent of Code 2020.
+*
+* Every `day_*` module exposes its parser and solver functions, plus a `Day*` type implementing
+* `solution::Solution` that parses the puzzle input once and answers either part. `registry`
+* lists the days so that they can be run by number, and the remaining modules provide the
+* input handling, diagnostics, timing and answer checking used by the command line.
+*
+* ```
+* use advent_2020::day_eighteen::{convert_to_post_fix, parse_expression, solve, OperationPrecedence};
+*
+* let tokens = parse_expression(1, "1 + 2 * 3 + 4").unwrap();
+* assert_eq!(solve(&convert_to_post_fix(&tokens, OperationPrecedence::None)), Some(13));
+* assert_eq!(solve(&convert_to_post_fix(&tokens, OperationPrecedence::AddWins)), Some(21));
+* ```
+*/
+
+pub mod bench;
+pub mod cancel;
+pub mod cli;
+pub mod diagnostics;
+pub mod differential;
+pub mod embedded;
+pub mod error;
+pub mod file_util;
+pub mod fixtures;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod memory;
+pub mod pool;
+pub mod random;
+pub mod registry;
+pub mod report;
+pub mod serve;
+pub mod solution;
+pub mod verify;
+pub mod day_one;
+pub mod day_two;
+pub mod day_three;
+pub mod day_four;
+pub mod day_five;
+pub mod day_six;
+pub mod day_seven;
+pub mod day_eight;
+pub mod day_nine;
+pub mod day_ten;
+pub mod day_eleven;
+pub mod day_twelve;
+pub mod day_thirteen;
+pub mod day_fourteen;
+pub mod day_fifteen;
+pub mod day_sixteen;
+pub mod day_seventeen;
+pub mod day_eighteen;
+pub mod day_nineteen;
+pub mod day_twenty;
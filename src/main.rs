@@ -1,28 +1,343 @@
-use crate::day_twenty::run_day_twenty;
-
-mod file_util;
-mod day_one;
-mod day_two;
-mod day_three;
-mod day_four;
-mod day_five;
-mod day_six;
-mod day_seven;
-mod day_eight;
-mod day_nine;
-mod day_ten;
-mod day_eleven;
-mod day_twelve;
-mod day_thirteen;
-mod day_fourteen;
-mod day_fifteen;
-mod day_sixteen;
-mod day_seventeen;
-mod day_eighteen;
-mod day_nineteen;
-mod day_twenty;
+use std::net::TcpListener;
+use std::process::exit;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use advent_2020::bench::{bench_day, render};
+use advent_2020::day_one::{DayOne, TARGET};
+use advent_2020::cancel::solve_within;
+use advent_2020::diagnostics::Diagnostic;
+use advent_2020::embedded;
+use advent_2020::error::Error;
+use advent_2020::generate::{generate, DEFAULT_SIZE};
+use advent_2020::cli::{BenchOptions, Command, GenerateOptions, RunOptions, Selection, VerifyOptions, parse_args};
+use advent_2020::input::{Assets, InputSource, ASSETS_VARIABLE};
+use advent_2020::memory;
+use advent_2020::registry::{self, DAYS, Entry, Solver};
+use advent_2020::pool::run_jobs;
+use advent_2020::report::{answer_record, error_record, memory_record, summary_table, DayOutcome, Output};
+use advent_2020::serve::serve;
+use advent_2020::solution::{Part, Solved, BOTH_PARTS};
+use advent_2020::verify::{check, Manifest, Outcome, ANSWERS_FILE};
 
-fn main() {
-    run_day_twenty()
+fn describe_parts(parts: &[Part]) -> String {
+    parts.iter().map(|part| part.to_string()).collect::<Vec<String>>().join(", ")
+}
+
+fn print_days() {
+    for entry in DAYS.iter() {
+        println!("Day {:>2}: parts {}", entry.day, describe_parts(entry.solution.parts()));
+    }
+}
+
+fn print_usage() {
+    println!("Usage:");
+    println!("  advent-2020 run <day|all> [--part <1|2>] [--input <file|->] [--assets <dir>] [--strict]");
+    println!("      [--format <text|json>] [--jobs <n>] [--timeout <time>] [--target <n>] [--count <n>]");
+    println!("  advent-2020 bench <day|all> [--runs <n>] [--format <table|json|csv>] [run options]");
+    println!("  advent-2020 verify [<day|all>] [--answers <file>] [--timeout <time>] [run options]");
+    println!("  advent-2020 generate <day> [--seed <n>] [--size <n>]");
+    println!("  advent-2020 serve [--port <n>]");
+    println!("  advent-2020 list");
+    println!("  advent-2020 help");
+    println!();
+    println!("Inputs default to <assets>/day_<n>, where <assets> is --assets, ${} or ./assets.", ASSETS_VARIABLE);
+    if embedded::ENABLED {
+        println!("This build embeds the assets, which are used unless --assets or ${} is set.", ASSETS_VARIABLE);
+    }
+    println!("Use --input - to read a single day's input from stdin.");
+    println!("Malformed lines are skipped and counted; --strict rejects the input and shows each one.");
+    println!("run --jobs solves days on n threads and prints a summary table once all are done.");
+    println!("run 1 --target n --count k answers day 1 with k entries adding up to n, as part 1.");
+    println!("run --format json prints one JSON object per answer or failed day.");
+    println!("--timeout gives each part at most that long, e.g. 500ms, 2s or 1m, then reports it as timed out.");
+    println!("verify compares answers with <assets>/{} unless --answers is given.", ANSWERS_FILE);
+    println!("generate prints a random valid input for a day, about --size records (default {}).", DEFAULT_SIZE);
+    println!("The same --seed always gives the same input; without one the seed is shown on stderr.");
+    println!("serve answers POST /day/<n>/part/<p> on localhost with JSON, the body being the input.");
+    if memory::ENABLED {
+        println!("This build counts allocations, and reports the memory each day and part uses.");
+    }
+    println!("bench times parse, part 1 and part 2 separately over --runs runs (default 10).");
+    println!();
+    println!("Implemented days:");
+    print_days();
+}
+
+fn summarise_skipped(day: u8, skipped: &[Diagnostic]) {
+    if skipped.is_empty() {
+        return;
+    }
+    let lines = skipped.iter()
+        .take(5)
+        .map(|it| it.line.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    let more = if skipped.len() > 5 { ", ..." } else { "" };
+    eprintln!(
+        "Day {}: skipped {} malformed line(s) ({}{}), run with --strict for details.",
+        day,
+        skipped.len(),
+        lines,
+        more
+    );
+}
+
+fn selected_parts(part: Option<Part>) -> Vec<Part> {
+    BOTH_PARTS.iter()
+        .copied()
+        .filter(|it| it.is_selected(part))
+        .collect()
+}
+
+fn solve_day(entry: &Entry, options: &RunOptions, source: &InputSource) -> Result<Solved, Error> {
+    let text = source.read_text()?;
+    let parts = selected_parts(options.part);
+    match options.timeout {
+        Some(budget) => solve_within(entry.solution.clone(), Arc::from(text), &parts, options.mode, budget),
+        None => entry.solution.solve(&text, &parts, options.mode)
+    }.map_err(|error| source.annotate(error))
+}
+
+/**
+* Runs a single day, reporting input problems on stderr. Returns whether the day succeeded,
+* which it did not if a part timed out.
+*/
+fn run_day(entry: &Entry, source: &InputSource, options: &RunOptions) -> bool {
+    if options.output == Output::Text {
+        println!("Day {}", entry.day);
+    }
+    match solve_day(entry, options, source) {
+        Ok(solved) => {
+            summarise_skipped(entry.day, &solved.skipped);
+            for answer in solved.answers.iter() {
+                match (options.output, &answer.value) {
+                    (Output::Json, _) => println!("{}", answer_record(entry.day, answer, source)),
+                    (Output::Text, _) if answer.timed_out => {
+                        println!("Part {}: Timed out after {:.2?}!", answer.part, answer.duration)
+                    },
+                    (Output::Text, Some(value)) => println!("Part {}: {}", answer.part, value),
+                    (Output::Text, None) => println!("Part {}: No answer!", answer.part)
+                }
+                if let (Output::Text, Some(memory)) = (options.output, answer.memory) {
+                    println!("  {}", memory);
+                }
+            }
+            match (options.output, solved.memory) {
+                (Output::Json, Some(memory)) => println!("{}", memory_record(entry.day, memory, source)),
+                (Output::Text, Some(memory)) => println!("Memory: {}", memory),
+                (_, None) => {}
+            }
+            DayOutcome::Solved(solved).succeeded()
+        },
+        Err(error) => {
+            if options.output == Output::Json {
+                println!("{}", error_record(entry.day, &error.to_string(), source));
+            }
+            eprintln!("Day {} failed:\n{}", entry.day, error);
+            false
+        }
+    }
+}
+
+/**
+* Day one looking for the target and count given on the command line, if either was.
+*/
+fn configure(entry: &Entry, options: &RunOptions) -> Entry {
+    if entry.day != 1 || (options.target.is_none() && options.count.is_none()) {
+        return entry.clone();
+    }
+    let report = DayOne::reconciling(options.target.unwrap_or(TARGET), options.count);
+    Entry { solution: Solver::Configured(Arc::new(report)), ..entry.clone() }
 }
 
+/**
+* The days a run or bench covers, each with the input it reads. Running everything skips days
+* that lack the selected part.
+*/
+fn select_days(options: &RunOptions) -> Result<Vec<(Entry, InputSource)>, String> {
+    let part = options.part;
+    let assets = Assets::resolve(options.assets.as_deref());
+    match options.selection {
+        Selection::All => Ok(
+            DAYS.iter()
+                .filter(|entry| part.is_none_or(|it| entry.solution.parts().contains(&it)))
+                .map(|entry| (entry.clone(), assets.source(entry.name)))
+                .collect()
+        ),
+        Selection::Day(number) => {
+            let entry = registry::get(number)
+                .map(|entry| configure(entry, options))
+                .ok_or(format!("Day {} is not implemented.", number))?;
+            if let Some(selected) = part.filter(|it| !entry.solution.parts().contains(it)) {
+                return Err(format!("Day {} part {} is not implemented.", number, selected));
+            }
+            let source = options.input.clone().unwrap_or_else(|| assets.source(entry.name));
+            Ok(vec!((entry, source)))
+        }
+    }
+}
+
+/**
+* Solves the selected days on `jobs` threads, then reports them in day order once all are done.
+* A day that panics is reported as failed without stopping the others.
+*/
+fn run_concurrently(options: &RunOptions, jobs: usize) -> Result<bool, String> {
+    let days = select_days(options)?;
+    let start = Instant::now();
+    let outcomes = run_jobs(&days, jobs, |(entry, source)| solve_day(entry, options, source))
+        .into_iter()
+        .map(DayOutcome::from_job)
+        .collect::<Vec<DayOutcome>>();
+    let elapsed = start.elapsed();
+    let mut summary = Vec::new();
+    for ((entry, source), outcome) in days.iter().zip(outcomes) {
+        match (&outcome, options.output) {
+            (DayOutcome::Solved(solved), Output::Json) => {
+                summarise_skipped(entry.day, &solved.skipped);
+                solved.answers.iter().for_each(|answer| println!("{}", answer_record(entry.day, answer, source)));
+                if let Some(memory) = solved.memory {
+                    println!("{}", memory_record(entry.day, memory, source));
+                }
+            },
+            (DayOutcome::Solved(solved), Output::Text) => summarise_skipped(entry.day, &solved.skipped),
+            (DayOutcome::Failed(error), output) => {
+                if output == Output::Json {
+                    println!("{}", error_record(entry.day, &error.to_string(), source));
+                }
+                eprintln!("Day {} failed:\n{}", entry.day, error);
+            },
+            (DayOutcome::Panicked(message), output) => {
+                if output == Output::Json {
+                    println!("{}", error_record(entry.day, &format!("panicked: {}", message), source));
+                }
+                eprintln!("Day {} panicked: {}", entry.day, message);
+            }
+        }
+        summary.push((entry.day, outcome));
+    }
+    if options.output == Output::Text {
+        print!("{}", summary_table(&summary, elapsed));
+    }
+    Ok(summary.iter().all(|(_, outcome)| outcome.succeeded()))
+}
+
+fn run(options: RunOptions) -> Result<bool, String> {
+    if let Some(jobs) = options.jobs {
+        return run_concurrently(&options, jobs);
+    }
+    let failures = select_days(&options)?
+        .iter()
+        .filter(|(entry, source)| !run_day(entry, source, &options))
+        .count();
+    Ok(failures == 0)
+}
+
+fn bench(options: BenchOptions) -> Result<bool, String> {
+    let parts = selected_parts(options.run.part);
+    let mut rows = Vec::new();
+    let mut failures = 0;
+    for (entry, source) in select_days(&options.run)? {
+        let benched = source.read_text()
+            .and_then(|text| bench_day(&entry, &text, &parts, options.run.mode, options.runs))
+            .map_err(|error| source.annotate(error));
+        match benched {
+            Ok(day_rows) => rows.extend(day_rows),
+            Err(error) => {
+                eprintln!("Day {} failed:\n{}", entry.day, error);
+                failures += 1;
+            }
+        }
+    }
+    print!("{}", render(&rows, options.format));
+    Ok(failures == 0)
+}
+
+fn verify(options: VerifyOptions) -> Result<bool, String> {
+    let answers = options.answers.clone()
+        .map(InputSource::File)
+        .unwrap_or_else(|| Assets::resolve(options.run.assets.as_deref()).source(ANSWERS_FILE));
+    let manifest = match Manifest::read(&answers) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            eprintln!("Could not read answers:\n{}", error);
+            return Ok(false);
+        }
+    };
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for (entry, source) in select_days(&options.run)? {
+        let solved = solve_day(&entry, &options.run, &source);
+        match solved {
+            Ok(solved) => for answer in solved.answers {
+                let part = answer.part;
+                let outcome = check(&manifest, entry.day, answer);
+                println!("Day {:>2} part {}: {}", entry.day, part, outcome);
+                match outcome {
+                    Outcome::Pass => passed += 1,
+                    Outcome::Fail { .. } | Outcome::TimedOut { .. } => failed += 1,
+                    Outcome::Missing { .. } => missing += 1
+                }
+            },
+            Err(error) => {
+                eprintln!("Day {} failed:\n{}", entry.day, error);
+                failed += 1;
+            }
+        }
+    }
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    Ok(failed == 0)
+}
+
+fn generate_input(options: GenerateOptions) -> Result<bool, String> {
+    let seed = options.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|it| it.as_nanos() as u64).unwrap_or_default();
+        eprintln!("Seed: {}", seed);
+        seed
+    });
+    let text = generate(options.day, seed, options.size)
+        .ok_or(format!("Day {} is not implemented.", options.day))?;
+    print!("{}", text);
+    Ok(true)
+}
+
+fn serve_on(port: u16) -> Result<bool, String> {
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on port {}: {}", port, error);
+            return Ok(false);
+        }
+    };
+    if let Ok(address) = listener.local_addr() {
+        eprintln!("Listening on http://{}", address);
+    }
+    serve(listener);
+    Ok(true)
+}
+
+fn main() {
+    let result = parse_args(std::env::args().skip(1))
+        .and_then(|command| match command {
+            Command::Run(options) => run(options),
+            Command::Bench(options) => bench(options),
+            Command::Verify(options) => verify(options),
+            Command::Generate(options) => generate_input(options),
+            Command::Serve { port } => serve_on(port),
+            Command::List => {
+                print_days();
+                Ok(true)
+            },
+            Command::Help => {
+                print_usage();
+                Ok(true)
+            }
+        });
+    match result {
+        Ok(true) => {},
+        Ok(false) => exit(1),
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("Run 'advent-2020 help' for usage.");
+            exit(2);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+
+/**
+* Whether this build counts allocations. Without the `count-allocations` feature the system
+* allocator is used as is and nothing is measured.
+*/
+pub const ENABLED: bool = cfg!(feature = "count-allocations");
+
+/**
+* What a piece of work allocated on its own thread. Each reallocation counts as an allocation of
+* the new size. `peak_bytes` is the most that was live at once, over what was live beforehand.
+*/
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub struct Memory {
+    pub allocations: u64,
+    pub bytes: u64,
+    pub peak_bytes: u64
+}
+
+impl Memory {
+    /**
+    * The memory used by this and then `next`, which frees everything it allocated before
+    * `next` starts.
+    */
+    pub fn followed_by(self, next: Memory) -> Memory {
+        Memory {
+            allocations: self.allocations + next.allocations,
+            bytes: self.bytes + next.bytes,
+            peak_bytes: self.peak_bytes.max(next.peak_bytes)
+        }
+    }
+}
+
+/**
+* A byte count in the largest binary unit that keeps it at least one.
+*/
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["KiB", "MiB", "GiB", "TiB"];
+    let mut scaled = bytes as f64;
+    let mut unit = None;
+    for next in units.iter() {
+        if scaled < 1024.0 {
+            break;
+        }
+        scaled /= 1024.0;
+        unit = Some(next);
+    }
+    match unit {
+        Some(unit) => format!("{:.2} {}", scaled, unit),
+        None => format!("{} B", bytes)
+    }
+}
+
+impl fmt::Display for Memory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocation{}, {} allocated, {} peak",
+            self.allocations,
+            if self.allocations == 1 { "" } else { "s" },
+            format_bytes(self.bytes),
+            format_bytes(self.peak_bytes)
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Counters {
+    allocations: u64,
+    bytes: u64,
+    live: u64,
+    peak: u64
+}
+
+thread_local! {
+    static COUNTERS: Cell<Counters> = const { Cell::new(Counters { allocations: 0, bytes: 0, live: 0, peak: 0 }) };
+}
+
+fn update(change: impl FnOnce(&mut Counters)) {
+    let _ = COUNTERS.try_with(|counters| {
+        let mut current = counters.get();
+        change(&mut current);
+        counters.set(current);
+    });
+}
+
+fn allocated(size: usize) {
+    update(|counters| {
+        counters.allocations += 1;
+        counters.bytes += size as u64;
+        counters.live += size as u64;
+        counters.peak = counters.peak.max(counters.live);
+    })
+}
+
+/**
+* Memory is sometimes freed by a different thread than allocated it, so a thread's live count
+* stops at zero rather than wrapping.
+*/
+fn freed(size: usize) {
+    update(|counters| counters.live = counters.live.saturating_sub(size as u64))
+}
+
+/**
+* The system allocator, counting what each thread allocates and frees. The counters are plain
+* thread locals, so counting takes no locks and allocates nothing itself.
+*/
+pub struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            allocated(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            allocated(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        freed(layout.size());
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let moved = System.realloc(pointer, layout, new_size);
+        if !moved.is_null() {
+            freed(layout.size());
+            allocated(new_size);
+        }
+        moved
+    }
+}
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/**
+* Runs `work`, returning what it allocated on this thread, or `None` if this build does not
+* count allocations. Measurements can be nested.
+*/
+pub fn measure<T>(work: impl FnOnce() -> T) -> (T, Option<Memory>) {
+    if !ENABLED {
+        return (work(), None);
+    }
+    let before = COUNTERS.with(Cell::get);
+    update(|counters| counters.peak = counters.live);
+    let result = work();
+    let after = COUNTERS.with(Cell::get);
+    update(|counters| counters.peak = counters.peak.max(before.peak));
+    let memory = Memory {
+        allocations: after.allocations - before.allocations,
+        bytes: after.bytes - before.bytes,
+        peak_bytes: after.peak.saturating_sub(before.live)
+    };
+    (result, Some(memory))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::*;
+
+    #[test]
+    fn should_measure_only_when_enabled() {
+        let ((inner, _), outer) = measure(|| {
+            let (kept, inner) = measure(|| vec!(0_u8; 4096));
+            drop(kept);
+            (inner, vec!(0_u8; 16))
+        });
+        assert_eq!(inner.is_some(), ENABLED);
+        if let (Some(inner), Some(outer)) = (inner, outer) {
+            assert_eq!((inner.allocations, inner.bytes, inner.peak_bytes), (1, 4096, 4096));
+            assert!(outer.allocations >= 2 && outer.bytes >= 4112 && outer.peak_bytes >= 4096);
+        }
+    }
+
+    #[test]
+    fn should_describe_memory() {
+        let memory = Memory { allocations: 3, bytes: 1536, peak_bytes: 512 }
+            .followed_by(Memory { allocations: 1, bytes: 3 << 20, peak_bytes: 1 << 20 });
+        assert_eq!(memory.to_string(), "4 allocations, 3.00 MiB allocated, 1.00 MiB peak");
+        assert_eq!(format_bytes(1536), "1.50 KiB")
+    }
+}
@@ -0,0 +1,60 @@
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+pub fn describe_panic(payload: Box<dyn Any + Send>) -> String {
+    payload.downcast_ref::<&str>()
+        .map(|message| String::from(*message))
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown panic"))
+}
+
+/**
+* Runs `work` over every job on up to `threads` threads, returning the results in job order.
+* A job that panics gives its panic message instead of stopping the other jobs.
+*/
+pub fn run_jobs<T, R, F>(jobs: &[T], threads: usize, work: F) -> Vec<Result<R, String>>
+    where T: Sync, R: Send, F: Fn(&T) -> R + Sync {
+    let next = AtomicUsize::new(0);
+    let mut finished = thread::scope(|scope| {
+        let workers = (0..threads.clamp(1, jobs.len().max(1)))
+            .map(|_| scope.spawn(|| {
+                let mut done = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let job = match jobs.get(index) {
+                        Some(job) => job,
+                        None => return done
+                    };
+                    done.push((index, catch_unwind(AssertUnwindSafe(|| work(job))).map_err(describe_panic)));
+                }
+            }))
+            .collect::<Vec<_>>();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect::<Vec<(usize, Result<R, String>)>>()
+    });
+    finished.sort_by_key(|(index, _)| *index);
+    finished.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pool::*;
+
+    #[test]
+    fn should_keep_job_order_and_isolate_panics() {
+        let jobs = (1..=20).collect::<Vec<u32>>();
+        let results = run_jobs(&jobs, 4, |job| {
+            if *job == 7 {
+                panic!("seven is broken");
+            }
+            job * 2
+        });
+        assert_eq!(results.len(), 20);
+        assert_eq!(results[0], Ok(2));
+        assert_eq!(results[6], Err(String::from("seven is broken")));
+        assert_eq!(results[19], Ok(40))
+    }
+}
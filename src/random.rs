@@ -0,0 +1,76 @@
+/**
+* A small seeded generator (SplitMix64), so that generated inputs can be reproduced from their
+* seed without pulling in a dependency. Not suitable for anything needing real randomness.
+*/
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    /**
+    * A number in `0..bound`, or zero when `bound` is zero.
+    */
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /**
+    * A number between `low` and `high`, inclusive.
+    */
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    /**
+    * True once in every `one_in` calls on average.
+    */
+    pub fn chance(&mut self, one_in: usize) -> bool {
+        self.below(one_in) == 0
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::*;
+
+    #[test]
+    fn should_repeat_for_the_same_seed() {
+        let mut random = Random::new(42);
+        let mut other = Random::new(42);
+        assert!((0..100).all(|_| random.next_u64() == other.next_u64()));
+        assert_ne!(Random::new(1).next_u64(), Random::new(2).next_u64())
+    }
+
+    #[test]
+    fn should_stay_in_range() {
+        let mut random = Random::new(7);
+        assert!((0..1000).map(|_| random.range(3, 5)).all(|it| (3..=5).contains(&it)));
+        assert_eq!(random.below(0), 0)
+    }
+}
@@ -0,0 +1,92 @@
+use std::ops::Deref;
+use std::sync::Arc;
+use crate::generate::{self, Generator};
+use crate::solution::Day;
+use crate::day_one::DayOne;
+use crate::day_two::DayTwo;
+use crate::day_three::DayThree;
+use crate::day_four::DayFour;
+use crate::day_five::DayFive;
+use crate::day_six::DaySix;
+use crate::day_seven::DaySeven;
+use crate::day_eight::DayEight;
+use crate::day_nine::DayNine;
+use crate::day_ten::DayTen;
+use crate::day_eleven::DayEleven;
+use crate::day_twelve::DayTwelve;
+use crate::day_thirteen::DayThirteen;
+use crate::day_fourteen::DayFourteen;
+use crate::day_fifteen::DayFifteen;
+use crate::day_sixteen::DaySixteen;
+use crate::day_seventeen::DaySeventeen;
+use crate::day_eighteen::DayEighteen;
+use crate::day_nineteen::DayNineteen;
+use crate::day_twenty::DayTwenty;
+
+/**
+* A day's solver: one of the registered statics, or one built at run time such as day one with
+* another target. Either can be handed to a thread that outlives its caller.
+*/
+#[derive(Clone)]
+pub enum Solver {
+    Registered(&'static dyn Day),
+    Configured(Arc<dyn Day + Send>)
+}
+
+impl Deref for Solver {
+    type Target = dyn Day;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Solver::Registered(day) => *day,
+            Solver::Configured(day) => day.as_ref()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Entry {
+    pub day: u8,
+    pub name: &'static str,
+    pub solution: Solver,
+    pub generator: Generator
+}
+
+pub static DAYS: [Entry; 20] = [
+    Entry { day: 1, name: "day_one", solution: Solver::Registered(&DayOne::new()), generator: generate::day_one },
+    Entry { day: 2, name: "day_two", solution: Solver::Registered(&DayTwo), generator: generate::day_two },
+    Entry { day: 3, name: "day_three", solution: Solver::Registered(&DayThree), generator: generate::day_three },
+    Entry { day: 4, name: "day_four", solution: Solver::Registered(&DayFour), generator: generate::day_four },
+    Entry { day: 5, name: "day_five", solution: Solver::Registered(&DayFive), generator: generate::day_five },
+    Entry { day: 6, name: "day_six", solution: Solver::Registered(&DaySix), generator: generate::day_six },
+    Entry { day: 7, name: "day_seven", solution: Solver::Registered(&DaySeven), generator: generate::day_seven },
+    Entry { day: 8, name: "day_eight", solution: Solver::Registered(&DayEight), generator: generate::day_eight },
+    Entry { day: 9, name: "day_nine", solution: Solver::Registered(&DayNine), generator: generate::day_nine },
+    Entry { day: 10, name: "day_ten", solution: Solver::Registered(&DayTen), generator: generate::day_ten },
+    Entry { day: 11, name: "day_eleven", solution: Solver::Registered(&DayEleven), generator: generate::day_eleven },
+    Entry { day: 12, name: "day_twelve", solution: Solver::Registered(&DayTwelve), generator: generate::day_twelve },
+    Entry { day: 13, name: "day_thirteen", solution: Solver::Registered(&DayThirteen), generator: generate::day_thirteen },
+    Entry { day: 14, name: "day_fourteen", solution: Solver::Registered(&DayFourteen), generator: generate::day_fourteen },
+    Entry { day: 15, name: "day_fifteen", solution: Solver::Registered(&DayFifteen), generator: generate::day_fifteen },
+    Entry { day: 16, name: "day_sixteen", solution: Solver::Registered(&DaySixteen), generator: generate::day_sixteen },
+    Entry { day: 17, name: "day_seventeen", solution: Solver::Registered(&DaySeventeen), generator: generate::day_seventeen },
+    Entry { day: 18, name: "day_eighteen", solution: Solver::Registered(&DayEighteen), generator: generate::day_eighteen },
+    Entry { day: 19, name: "day_nineteen", solution: Solver::Registered(&DayNineteen), generator: generate::day_nineteen },
+    Entry { day: 20, name: "day_twenty", solution: Solver::Registered(&DayTwenty), generator: generate::day_twenty }
+];
+
+pub fn get(day: u8) -> Option<&'static Entry> {
+    DAYS.iter().find(|entry| entry.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::*;
+
+    #[test]
+    fn should_register_every_day_in_order() {
+        assert!(DAYS.iter().map(|entry| entry.day).eq(1..=20));
+        assert_eq!(get(7).map(|entry| entry.name), Some("day_seven"));
+        assert!(get(21).is_none())
+    }
+}
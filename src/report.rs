@@ -0,0 +1,261 @@
+use std::str::FromStr;
+use std::time::Duration;
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::memory::Memory;
+use crate::solution::{Answer, Solved};
+
+/**
+* How `run` prints results. `Json` writes one JSON object per line and nothing else to stdout.
+*/
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Output {
+    Text, Json
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            _ => Err(format!("Unknown format '{}', expected text or json.", value))
+        }
+    }
+}
+
+/**
+* Quotes a string as a JSON string literal.
+*/
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            control if control.is_control() => quoted.push_str(&format!("\\u{:04x}", control as u32)),
+            _ => quoted.push(character)
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn describe_input(source: &InputSource) -> String {
+    match source {
+        InputSource::File(path) => quote(&path.display().to_string()),
+        InputSource::Stdin => String::from("null"),
+        InputSource::Embedded(_) => quote(&source.to_string())
+    }
+}
+
+fn memory_fields(memory: Option<Memory>) -> String {
+    memory.map(|memory| format!(
+        "\"allocations\": {}, \"allocated_bytes\": {}, \"peak_bytes\": {}, ",
+        memory.allocations,
+        memory.bytes,
+        memory.peak_bytes
+    )).unwrap_or_default()
+}
+
+/**
+* Answers are strings because several do not fit in a JSON number without losing precision.
+* A part with no answer has a null `answer`, and stdin has a null `input`. A part that ran out
+* of time also has `"timed_out": true`, and builds that count allocations add `allocations`,
+* `allocated_bytes` and `peak_bytes`.
+*/
+pub fn answer_record(day: u8, answer: &Answer, source: &InputSource) -> String {
+    format!(
+        "{{\"day\": {}, \"part\": {}, \"answer\": {}, \"duration_ns\": {}, {}{}\"input\": {}}}",
+        day,
+        answer.part,
+        answer.value.as_deref().map(quote).unwrap_or_else(|| String::from("null")),
+        answer.duration.as_nanos(),
+        if answer.timed_out { "\"timed_out\": true, " } else { "" },
+        memory_fields(answer.memory),
+        describe_input(source)
+    )
+}
+
+/**
+* The memory a whole day used, parsing included, in the same fields as `answer_record`.
+*/
+pub fn memory_record(day: u8, memory: Memory, source: &InputSource) -> String {
+    format!("{{\"day\": {}, {}\"input\": {}}}", day, memory_fields(Some(memory)), describe_input(source))
+}
+
+pub fn error_record(day: u8, message: &str, source: &InputSource) -> String {
+    format!(
+        "{{\"day\": {}, \"error\": {}, \"input\": {}}}",
+        day,
+        quote(message),
+        describe_input(source)
+    )
+}
+
+/**
+* How a single day ended when run alongside others.
+*/
+#[derive(Debug)]
+pub enum DayOutcome {
+    Solved(Solved),
+    Failed(Error),
+    Panicked(String)
+}
+
+impl DayOutcome {
+    pub fn from_job(job: Result<Result<Solved, Error>, String>) -> Self {
+        match job {
+            Ok(Ok(solved)) => DayOutcome::Solved(solved),
+            Ok(Err(error)) => DayOutcome::Failed(error),
+            Err(message) => DayOutcome::Panicked(message)
+        }
+    }
+
+    /**
+    * A day with a part that timed out did not succeed, even though its other parts may have.
+    */
+    pub fn succeeded(&self) -> bool {
+        matches!(self, DayOutcome::Solved(solved) if solved.answers.iter().all(|answer| !answer.timed_out))
+    }
+}
+
+fn describe_answer(answer: &Answer) -> &str {
+    match &answer.value {
+        _ if answer.timed_out => "Timed out!",
+        Some(value) => value,
+        None => "No answer!"
+    }
+}
+
+/**
+* One row per answer in day order, then a totals line. A failed day gets a single row with the
+* first line of its error. Times are per part and exclude parsing; a part that timed out counts
+* the time it was given. Memory, when counted, follows each part's time, and a day's total
+* including parsing gets a row of its own.
+*/
+pub fn summary_table(days: &[(u8, DayOutcome)], elapsed: Duration) -> String {
+    let mut table = format!("{:>3}  {:>4}  {:<20} {:>12}\n", "Day", "Part", "Answer", "Time");
+    let (mut answers, mut failures, mut timeouts) = (0, 0, 0);
+    let mut solving = Duration::ZERO;
+    for (day, outcome) in days {
+        match outcome {
+            DayOutcome::Solved(solved) => {
+                for answer in solved.answers.iter() {
+                    if answer.timed_out {
+                        timeouts += 1;
+                    } else {
+                        answers += 1;
+                    }
+                    solving += answer.duration;
+                    table.push_str(&format!(
+                        "{:>3}  {:>4}  {:<20} {:>12}{}\n",
+                        day,
+                        answer.part.to_string(),
+                        describe_answer(answer),
+                        format!("{:.2?}", answer.duration),
+                        answer.memory.map(|memory| format!("  {}", memory)).unwrap_or_default()
+                    ));
+                }
+                if let Some(memory) = solved.memory {
+                    table.push_str(&format!("{:>3}  {:>4}  memory: {}\n", day, "-", memory));
+                }
+            },
+            DayOutcome::Failed(error) => {
+                failures += 1;
+                let reason = error.to_string();
+                let reason = reason.lines().next().unwrap_or_default();
+                table.push_str(&format!("{:>3}  {:>4}  failed: {}\n", day, "-", reason));
+            },
+            DayOutcome::Panicked(message) => {
+                failures += 1;
+                table.push_str(&format!("{:>3}  {:>4}  panicked: {}\n", day, "-", message));
+            }
+        }
+    }
+    table.push_str(&format!(
+        "{} answer{} from {} day{}, {} failed, {} timed out, {:.2?} solving, {:.2?} elapsed\n",
+        answers,
+        if answers == 1 { "" } else { "s" },
+        days.len() - failures,
+        if days.len() - failures == 1 { "" } else { "s" },
+        failures,
+        timeouts,
+        solving,
+        elapsed
+    ));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report::*;
+    use crate::solution::Part;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn should_summarise_days_in_order() {
+        let solved = Solved {
+            answers: vec!(
+                Answer { part: Part::One, value: Some(String::from("514579")), duration: Duration::from_millis(2), timed_out: false, memory: None },
+                Answer { part: Part::Two, value: None, duration: Duration::from_secs(1), timed_out: true, memory: None }
+            ),
+            skipped: Vec::new(),
+            memory: None
+        };
+        let table = summary_table(
+            &[(1, DayOutcome::Solved(solved)), (20, DayOutcome::Panicked(String::from("unfinished")))],
+            Duration::from_millis(1003)
+        );
+        assert_eq!(
+            table.lines().collect::<Vec<&str>>(),
+            vec!(
+                "Day  Part  Answer                       Time",
+                "  1     1  514579                     2.00ms",
+                "  1     2  Timed out!                  1.00s",
+                " 20     -  panicked: unfinished",
+                "1 answer from 1 day, 1 failed, 1 timed out, 1.00s solving, 1.00s elapsed"
+            )
+        )
+    }
+
+    #[test]
+    fn should_quote_json_strings() {
+        assert_eq!(quote("a \"b\"\\\n\u{1}"), "\"a \\\"b\\\"\\\\\\n\\u0001\"")
+    }
+
+    #[test]
+    fn should_render_answer_record() {
+        let answer = Answer { part: Part::Two, value: Some(String::from("158493")), duration: Duration::from_micros(5), timed_out: false, memory: None };
+        assert_eq!(
+            answer_record(7, &answer, &InputSource::File(PathBuf::from("assets/day_seven"))),
+            "{\"day\": 7, \"part\": 2, \"answer\": \"158493\", \"duration_ns\": 5000, \"input\": \"assets/day_seven\"}"
+        );
+        let answer = Answer { part: Part::One, value: None, duration: Duration::ZERO, timed_out: false, memory: None };
+        assert_eq!(
+            answer_record(7, &answer, &InputSource::Stdin),
+            "{\"day\": 7, \"part\": 1, \"answer\": null, \"duration_ns\": 0, \"input\": null}"
+        );
+        let answer = Answer { part: Part::One, value: None, duration: Duration::from_secs(2), timed_out: true, memory: None };
+        assert_eq!(
+            answer_record(7, &answer, &InputSource::Stdin),
+            "{\"day\": 7, \"part\": 1, \"answer\": null, \"duration_ns\": 2000000000, \"timed_out\": true, \"input\": null}"
+        );
+        let memory = Memory { allocations: 2, bytes: 96, peak_bytes: 64 };
+        let answer = Answer { part: Part::One, value: Some(String::from("1")), duration: Duration::ZERO, timed_out: false, memory: Some(memory) };
+        assert_eq!(
+            answer_record(7, &answer, &InputSource::Stdin),
+            "{\"day\": 7, \"part\": 1, \"answer\": \"1\", \"duration_ns\": 0, \"allocations\": 2, \"allocated_bytes\": 96, \"peak_bytes\": 64, \"input\": null}"
+        );
+        assert_eq!(
+            memory_record(7, memory, &InputSource::Stdin),
+            "{\"day\": 7, \"allocations\": 2, \"allocated_bytes\": 96, \"peak_bytes\": 64, \"input\": null}"
+        )
+    }
+}
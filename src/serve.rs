@@ -0,0 +1,386 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::cancel::solve_within;
+use crate::diagnostics::Mode;
+use crate::error::Error;
+use crate::file_util::decode;
+use crate::pool::describe_panic;
+use crate::registry;
+use crate::report::quote;
+use crate::solution::Part;
+
+pub const DEFAULT_PORT: u16 = 2020;
+
+const MAX_LINE: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+const MAX_BODY: usize = 16 * 1024 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_CONNECTIONS: usize = 16;
+
+/**
+* How long a request's part may take before it is answered with a 503 and asked to stop.
+*/
+pub const SOLVE_BUDGET: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>
+}
+
+/**
+* Every response is JSON, and the connection is closed once it has been sent.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Self {
+        Response { status, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response::json(status, format!("{{\"error\": {}}}", quote(message)))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error"
+        }
+    }
+
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "HTTP/1.1 {} {}\r\n", self.status, self.reason())?;
+        write!(out, "Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n", self.body.len() + 1)?;
+        if self.status == 405 {
+            write!(out, "Allow: POST\r\n")?;
+        }
+        write!(out, "\r\n{}\n", self.body)?;
+        out.flush()
+    }
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, Response> {
+    let mut line = Vec::new();
+    reader.by_ref()
+        .take(MAX_LINE)
+        .read_until(b'\n', &mut line)
+        .map_err(|error| Response::error(400, &format!("Could not read the request: {}", error)))?;
+    if !line.ends_with(b"\n") {
+        return Err(Response::error(400, "The request ended early, or has a line longer than 8 KiB."));
+    }
+    String::from_utf8(line)
+        .map(|line| String::from(line.trim_end()))
+        .map_err(|_| Response::error(400, "The request line and headers must be UTF-8."))
+}
+
+/**
+* Reads a request line, its headers and a body of `Content-Length` bytes. Clients that send
+* `Expect: 100-continue` are told to go ahead on `writer`. A request that cannot be read gives
+* the error response to send instead.
+*/
+pub fn read_request(reader: &mut impl BufRead, writer: &mut impl Write) -> Result<Request, Response> {
+    let request_line = read_line(reader)?;
+    let mut words = request_line.split(' ');
+    let (method, path) = match (words.next(), words.next(), words.next(), words.next()) {
+        (Some(method), Some(path), Some(version), None) if version.starts_with("HTTP/1.") => (method, path),
+        _ => return Err(Response::error(400, "Malformed request line."))
+    };
+    let (mut length, mut expect_continue) = (None, false);
+    let mut headers = 0;
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Err(Response::error(400, "Too many headers."));
+        }
+        let (name, value) = line.split_once(':')
+            .ok_or_else(|| Response::error(400, &format!("Malformed header '{}'.", line)))?;
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => length = Some(
+                value.trim().parse::<usize>()
+                    .map_err(|_| Response::error(400, &format!("Invalid Content-Length '{}'.", value.trim())))?
+            ),
+            "transfer-encoding" => return Err(Response::error(411, "Chunked bodies are not supported, send a Content-Length.")),
+            "expect" => expect_continue = value.trim().eq_ignore_ascii_case("100-continue"),
+            _ => {}
+        }
+    }
+    let length = match (method, length) {
+        (_, Some(length)) => length,
+        ("POST", None) => return Err(Response::error(411, "Missing Content-Length.")),
+        (_, None) => 0
+    };
+    if length > MAX_BODY {
+        return Err(Response::error(413, "Inputs are limited to 16 MiB."));
+    }
+    if expect_continue && length > 0 {
+        writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+            .and_then(|_| writer.flush())
+            .map_err(|error| Response::error(400, &format!("Could not read the request: {}", error)))?;
+    }
+    let mut body = vec!(0; length);
+    reader.read_exact(&mut body)
+        .map_err(|_| Response::error(400, "The body is shorter than its Content-Length."))?;
+    Ok(Request { method: String::from(method), path: String::from(path), body })
+}
+
+fn parse_route(path: &str) -> Option<(u8, Part)> {
+    let (day, part) = path.strip_prefix("/day/")?.split_once("/part/")?;
+    Some((day.parse().ok()?, part.parse().ok()?))
+}
+
+/**
+* A rejected input is a 422 listing each bad line, any other unreadable input is a 400.
+*/
+fn rejected(error: Error) -> Response {
+    match &error {
+        Error::Parse { diagnostics, .. } => {
+            let lines = diagnostics.iter()
+                .map(|diagnostic| format!(
+                    "{{\"line\": {}, \"column\": {}, \"width\": {}, \"reason\": {}}}",
+                    diagnostic.line,
+                    diagnostic.column,
+                    diagnostic.width,
+                    quote(&diagnostic.reason)
+                ))
+                .collect::<Vec<String>>();
+            Response::json(
+                422,
+                format!("{{\"error\": {}, \"diagnostics\": [{}]}}", quote(&error.to_string()), lines.join(", "))
+            )
+        },
+        Error::Io { .. } => Response::error(400, &error.to_string())
+    }
+}
+
+/**
+* Answers `POST /day/<n>/part/<p>`, whose body is the puzzle input. Inputs are parsed strictly,
+* so any malformed line is rejected. `duration_ns` times the part alone and `total_ns` includes
+* parsing. A part that takes longer than `budget` is answered with a 503.
+*/
+pub fn respond(request: Request, budget: Duration) -> Response {
+    let (day, part) = match parse_route(&request.path) {
+        Some(route) => route,
+        None => return Response::error(404, "Not found, expected POST /day/<n>/part/<1|2>.")
+    };
+    if request.method != "POST" {
+        return Response::error(405, "Send the input with POST.");
+    }
+    let entry = match registry::get(day) {
+        Some(entry) if entry.solution.parts().contains(&part) => entry,
+        Some(_) => return Response::error(404, &format!("Day {} part {} is not implemented.", day, part)),
+        None => return Response::error(404, &format!("Day {} is not implemented.", day))
+    };
+    let text = match decode(request.body, None) {
+        Ok(text) => text,
+        Err(error) => return Response::error(400, &error.to_string())
+    };
+    let start = Instant::now();
+    let solving = || solve_within(entry.solution.clone(), Arc::from(text), &[part], Mode::Strict, budget);
+    match catch_unwind(AssertUnwindSafe(solving)) {
+        Ok(Ok(solved)) if solved.answers[0].timed_out => Response::error(
+            503,
+            &format!("Day {} part {} took longer than {:.2?}.", day, part, budget)
+        ),
+        Ok(Ok(solved)) => {
+            let total = start.elapsed();
+            let answer = &solved.answers[0];
+            Response::json(200, format!(
+                "{{\"day\": {}, \"part\": {}, \"answer\": {}, \"duration_ns\": {}, \"total_ns\": {}}}",
+                day,
+                part,
+                answer.value.as_deref().map(quote).unwrap_or_else(|| String::from("null")),
+                answer.duration.as_nanos(),
+                total.as_nanos()
+            ))
+        },
+        Ok(Err(error)) => rejected(error),
+        Err(payload) => Response::error(500, &format!("Day {} part {} panicked: {}", day, part, describe_panic(payload)))
+    }
+}
+
+/**
+* Reads one request from `stream`, answers it and closes the connection, returning the request
+* line and status for logging.
+*/
+pub fn handle(stream: TcpStream) -> io::Result<String> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let (summary, response) = match read_request(&mut reader, &mut writer) {
+        Ok(request) => (format!("{} {}", request.method, request.path), respond(request, SOLVE_BUDGET)),
+        Err(response) => (String::from("-"), response)
+    };
+    response.write_to(&mut writer)?;
+    Ok(format!("{} {}", summary, response.status))
+}
+
+/**
+* Turns away a connection while the server is full, without reading the request.
+*/
+fn refuse(stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(READ_TIMEOUT))?;
+    Response::error(503, "Too many connections, try again later.").write_to(&mut &stream)
+}
+
+/**
+* Frees a connection's slot when its thread ends, however it ends.
+*/
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/**
+* Answers connections on `listener` until the process is stopped, each on its own thread so a
+* slow day does not hold up the others. At most `MAX_CONNECTIONS` are answered at once, and
+* any more are turned away with a 503. Each request is logged on stderr.
+*/
+pub fn serve(listener: TcpListener) {
+    let open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) if open.load(Ordering::SeqCst) >= MAX_CONNECTIONS => {
+                if let Err(error) = refuse(stream) {
+                    eprintln!("Connection failed: {}", error);
+                }
+                eprintln!("- 503");
+            },
+            Ok(stream) => {
+                open.fetch_add(1, Ordering::SeqCst);
+                let slot = Slot(Arc::clone(&open));
+                thread::spawn(move || {
+                    let _slot = slot;
+                    match handle(stream) {
+                        Ok(summary) => eprintln!("{}", summary),
+                        Err(error) => eprintln!("Connection failed: {}", error)
+                    }
+                });
+            },
+            Err(error) => eprintln!("Could not accept a connection: {}", error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serve::*;
+    use std::io::Cursor;
+    use std::net::Shutdown;
+
+    fn read(request: &str) -> Result<Request, Response> {
+        read_request(&mut Cursor::new(request.as_bytes()), &mut Vec::new())
+    }
+
+    fn post(path: &str, body: &str) -> Response {
+        respond(Request { method: String::from("POST"), path: String::from(path), body: Vec::from(body) }, SOLVE_BUDGET)
+    }
+
+    #[test]
+    fn should_read_request_with_body() {
+        assert_eq!(
+            read("POST /day/1/part/1 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 5\r\n\r\n1721\nextra"),
+            Ok(Request { method: String::from("POST"), path: String::from("/day/1/part/1"), body: Vec::from("1721\n") })
+        );
+        let mut written = Vec::new();
+        read_request(
+            &mut Cursor::new("POST / HTTP/1.1\r\nExpect: 100-continue\r\nContent-Length: 1\r\n\r\n1"),
+            &mut written
+        ).unwrap();
+        assert_eq!(written, b"HTTP/1.1 100 Continue\r\n\r\n")
+    }
+
+    #[test]
+    fn should_reject_malformed_requests() {
+        let status = |request: &str| read(request).err().map(|response| response.status);
+        assert_eq!(status("POST /day/1/part/1 HTTP/1.1\r\n\r\n"), Some(411));
+        assert_eq!(status("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"), Some(411));
+        assert_eq!(status("POST / HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n"), Some(413));
+        assert_eq!(status("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort"), Some(400));
+        assert_eq!(status("hello\r\n\r\n"), Some(400));
+        assert_eq!(status("POST / HTTP/1.1\r\nContent-Length: 1"), Some(400))
+    }
+
+    #[test]
+    fn should_answer_part() {
+        let response = post("/day/1/part/1", "1721\n979\n366\n299\n675\n1456\n");
+        assert_eq!(response.status, 200);
+        assert!(response.body.starts_with("{\"day\": 1, \"part\": 1, \"answer\": \"514579\", \"duration_ns\": "))
+    }
+
+    #[test]
+    fn should_reject_malformed_input_with_its_lines() {
+        let response = post("/day/2/part/1", "1-3 a: abcde\n1-x a: abc\n");
+        assert_eq!(response.status, 422);
+        assert!(response.body.contains("\"diagnostics\": [{\"line\": 2, \"column\": 3, \"width\": 1, \"reason\": "));
+        assert_eq!(
+            respond(Request { method: String::from("POST"), path: String::from("/day/1/part/1"), body: vec!(b'1', 0xff) }, SOLVE_BUDGET).status,
+            400
+        )
+    }
+
+    #[test]
+    fn should_answer_cyclic_input_without_overflowing() {
+        let response = post("/day/7/part/2", "shiny gold bags contain 1 dark red bag.\ndark red bags contain 1 shiny gold bag.\n");
+        assert_eq!((response.status, response.body.contains("\"answer\": null")), (200, true));
+        assert!(post("/day/19/part/1", "0: 1\n1: 0\n\na\n").body.contains("\"answer\": null"))
+    }
+
+    #[test]
+    fn should_give_up_on_parts_over_budget() {
+        let numbers = (1..=2000).map(|number| number.to_string()).collect::<Vec<String>>().join("\n");
+        let request = Request { method: String::from("POST"), path: String::from("/day/15/part/2"), body: Vec::from(numbers) };
+        assert_eq!(respond(request, Duration::from_millis(1)).status, 503)
+    }
+
+    #[test]
+    fn should_not_find_unknown_routes() {
+        assert_eq!(post("/days", "").status, 404);
+        assert_eq!(post("/day/25/part/1", "").status, 404);
+        assert_eq!(post("/day/19/part/2", "").status, 404);
+        assert_eq!(post("/day/1/part/3", "").status, 404);
+        assert_eq!(
+            respond(Request { method: String::from("GET"), path: String::from("/day/1/part/1"), body: Vec::new() }, SOLVE_BUDGET).status,
+            405
+        )
+    }
+
+    #[test]
+    fn should_answer_over_tcp() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || handle(listener.accept().unwrap().0).unwrap());
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(b"POST /day/6/part/2 HTTP/1.1\r\nContent-Length: 11\r\n\r\nab\nac\n\nb\nb\n").unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("\"answer\": \"2\""), "{}", response);
+        assert_eq!(server.join().unwrap(), "POST /day/6/part/2 200")
+    }
+}
@@ -0,0 +1,286 @@
+use std::fmt;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use crate::bench::Phase;
+use crate::diagnostics::{Diagnostic, Diagnostics, Mode};
+use crate::error::Error;
+use crate::memory::{self, Memory};
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Part {
+    One, Two
+}
+
+pub const BOTH_PARTS: &[Part] = &[Part::One, Part::Two];
+
+impl Part {
+    pub fn is_selected(self, selection: Option<Part>) -> bool {
+        selection.is_none_or(|selected| selected == self)
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("Unknown part '{}', expected 1 or 2.", value))
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2")
+        }
+    }
+}
+
+/**
+* A single day's puzzle: parse the input once, then solve either part from the parsed form.
+* A part returns `None` when the input has no answer.
+*
+* Parsers skip lines they cannot understand, recording why in `diagnostics`; whether that
+* fails the input is decided by the caller's `Mode`.
+*
+* The lines are borrowed from a single buffer holding the whole input, and the parsed form may
+* keep borrowing from it.
+*/
+pub trait Solution {
+    type Input<'a>;
+    type PartOne: fmt::Display;
+    type PartTwo: fmt::Display;
+
+    fn parse<'a>(
+        &self,
+        lines: impl Iterator<Item = &'a str>,
+        diagnostics: &mut Diagnostics
+    ) -> Result<Self::Input<'a>, Error>;
+
+    fn part_one(&self, input: &Self::Input<'_>) -> Option<Self::PartOne>;
+
+    fn part_two(&self, input: &Self::Input<'_>) -> Option<Self::PartTwo>;
+
+    /**
+    * The parts with a working implementation. Parts not listed are never run.
+    */
+    fn parts(&self) -> &'static [Part] {
+        BOTH_PARTS
+    }
+
+    /**
+    * The parts with a reference solver: a slow version written to be obviously correct, which
+    * `differential` checks the real solver against. Parts not listed have none.
+    */
+    fn reference_parts(&self) -> &'static [Part] {
+        &[]
+    }
+
+    fn reference_part_one(&self, _: &Self::Input<'_>) -> Option<Self::PartOne> {
+        None
+    }
+
+    fn reference_part_two(&self, _: &Self::Input<'_>) -> Option<Self::PartTwo> {
+        None
+    }
+}
+
+/**
+* A part's answer, and how long the part took to solve, not counting the shared parse. A part
+* that ran out of time has no value, and `duration` is how long it was given. `memory` is only
+* measured by builds that count allocations.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct Answer {
+    pub part: Part,
+    pub value: Option<String>,
+    pub duration: Duration,
+    pub timed_out: bool,
+    pub memory: Option<Memory>
+}
+
+/**
+* The answers for a day, plus any lines a lenient parse skipped. `memory` covers parsing and
+* every part together.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct Solved {
+    pub answers: Vec<Answer>,
+    pub skipped: Vec<Diagnostic>,
+    pub memory: Option<Memory>
+}
+
+/**
+* Object safe view of a `Solution` so that days with different input and answer types can
+* live side by side in the registry.
+*/
+pub trait Day: Sync {
+    fn parts(&self) -> &'static [Part];
+
+    fn solve(&self, text: &str, parts: &[Part], mode: Mode) -> Result<Solved, Error>;
+
+    /**
+    * Parses `text` and runs the selected parts once, timing each phase.
+    */
+    fn time(&self, text: &str, parts: &[Part], mode: Mode) -> Result<Vec<(Phase, Duration)>, Error>;
+
+    fn reference_parts(&self) -> &'static [Part];
+
+    /**
+    * Strictly parses `text` and answers `part` with both the solver and the reference solver.
+    */
+    fn compare(&self, text: &str, part: Part) -> Result<(Option<String>, Option<String>), Error>;
+}
+
+fn parse_in_mode<'a, S: Solution>(
+    solution: &S,
+    text: &'a str,
+    mode: Mode
+) -> Result<(S::Input<'a>, Vec<Diagnostic>), Error> {
+    let mut diagnostics = Diagnostics::new();
+    let input = solution.parse(text.lines(), &mut diagnostics)?;
+    let skipped = diagnostics.into_rejected();
+    if mode == Mode::Strict && !skipped.is_empty() {
+        return Err(Error::rejected(skipped));
+    }
+    Ok((input, skipped))
+}
+
+impl<S: Solution + Sync> Day for S {
+    fn parts(&self) -> &'static [Part] {
+        Solution::parts(self)
+    }
+
+    fn solve(&self, text: &str, parts: &[Part], mode: Mode) -> Result<Solved, Error> {
+        let (solved, memory) = memory::measure(|| {
+            let (input, skipped) = parse_in_mode(self, text, mode)?;
+            let answers = parts.iter()
+                .filter(|part| Solution::parts(self).contains(part))
+                .map(|part| {
+                    let start = Instant::now();
+                    let (value, memory) = memory::measure(|| match part {
+                        Part::One => self.part_one(&input).map(|it| it.to_string()),
+                        Part::Two => self.part_two(&input).map(|it| it.to_string())
+                    });
+                    Answer { part: *part, value, duration: start.elapsed(), timed_out: false, memory }
+                })
+                .collect();
+            Ok(Solved { answers, skipped, memory: None })
+        });
+        solved.map(|solved| Solved { memory, ..solved })
+    }
+
+    fn time(&self, text: &str, parts: &[Part], mode: Mode) -> Result<Vec<(Phase, Duration)>, Error> {
+        let start = Instant::now();
+        let (input, _) = parse_in_mode(self, text, mode)?;
+        let mut timings = vec!((Phase::Parse, start.elapsed()));
+        for part in parts.iter().filter(|part| Solution::parts(self).contains(part)) {
+            let start = Instant::now();
+            match part {
+                Part::One => { black_box(self.part_one(&input)); },
+                Part::Two => { black_box(self.part_two(&input)); }
+            }
+            timings.push((Phase::Part(*part), start.elapsed()));
+        }
+        Ok(timings)
+    }
+
+    fn reference_parts(&self) -> &'static [Part] {
+        Solution::reference_parts(self)
+    }
+
+    fn compare(&self, text: &str, part: Part) -> Result<(Option<String>, Option<String>), Error> {
+        let (input, _) = parse_in_mode(self, text, Mode::Strict)?;
+        Ok(match part {
+            Part::One => (
+                self.part_one(&input).map(|it| it.to_string()),
+                self.reference_part_one(&input).map(|it| it.to_string())
+            ),
+            Part::Two => (
+                self.part_two(&input).map(|it| it.to_string()),
+                self.reference_part_two(&input).map(|it| it.to_string())
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solution::*;
+    use crate::file_util::numbered_non_blank_lines;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        type Input<'a> = Vec<u32>;
+        type PartOne = u32;
+        type PartTwo = u32;
+
+        fn parse<'a>(&self, lines: impl Iterator<Item = &'a str>, diagnostics: &mut Diagnostics) -> Result<Self::Input<'a>, Error> {
+            Ok(
+                numbered_non_blank_lines(lines)
+                    .filter_map(|(number, line)| diagnostics.check(
+                        line.parse().map_err(|error| Diagnostic::new(number, line, error))
+                    ))
+                    .collect()
+            )
+        }
+
+        fn part_one(&self, input: &Self::Input<'_>) -> Option<u32> {
+            Some(input.iter().sum())
+        }
+
+        fn part_two(&self, _: &Self::Input<'_>) -> Option<u32> {
+            None
+        }
+    }
+
+    #[test]
+    fn should_solve_requested_parts_through_day() {
+        let day: &dyn Day = &Sum;
+        let solved = day.solve("1\n2\n", &[Part::Two, Part::One], Mode::Strict).unwrap();
+        assert_eq!(
+            solved.answers.into_iter()
+                .map(|answer| (answer.part, answer.value))
+                .collect::<Vec<(Part, Option<String>)>>(),
+            vec!((Part::Two, None), (Part::One, Some(String::from("3"))))
+        );
+        assert!(solved.skipped.is_empty())
+    }
+
+    #[test]
+    fn should_fail_strict_solve_on_bad_input() {
+        let day: &dyn Day = &Sum;
+        assert!(day.solve("1\ntwo\n", &[Part::One], Mode::Strict).is_err())
+    }
+
+    #[test]
+    fn should_skip_bad_input_in_lenient_solve() {
+        let day: &dyn Day = &Sum;
+        let solved = day.solve("1\ntwo\n4\n", &[Part::One], Mode::Lenient).unwrap();
+        assert_eq!(solved.answers[0].value, Some(String::from("5")));
+        assert_eq!(solved.skipped.iter().map(|it| it.line).collect::<Vec<usize>>(), vec!(2))
+    }
+
+    #[test]
+    fn should_time_each_selected_phase() {
+        let day: &dyn Day = &Sum;
+        let phases = day.time("1\n2\n", BOTH_PARTS, Mode::Strict).unwrap()
+            .into_iter()
+            .map(|(phase, _)| phase)
+            .collect::<Vec<Phase>>();
+        assert_eq!(phases, vec!(Phase::Parse, Phase::Part(Part::One), Phase::Part(Part::Two)))
+    }
+
+    #[test]
+    fn should_select_parts() {
+        assert!(Part::One.is_selected(None));
+        assert!(Part::One.is_selected(Some(Part::One)));
+        assert!(!Part::One.is_selected(Some(Part::Two)))
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::error::Error;
+use crate::file_util::numbered_lines;
+use crate::input::InputSource;
+use crate::solution::{Answer, Part};
+
+pub const ANSWERS_FILE: &str = "answers";
+
+/**
+* Recorded answers, one `<day> <part> <answer>` per line. Blank lines and lines starting with
+* `#` are ignored.
+*/
+#[derive(Debug, Eq, PartialEq, Default)]
+pub struct Manifest {
+    answers: HashMap<(u8, Part), String>
+}
+
+fn parse_expected(number: usize, line: &str) -> Result<((u8, Part), String), Diagnostic> {
+    let mut fields = line.split_whitespace();
+    let day = fields.next().unwrap_or(line);
+    let day = u8::from_str(day)
+        .map_err(|error| Diagnostic::at(number, line, day, error))?;
+    let part = fields.next()
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected a part"))?;
+    let part = Part::from_str(part)
+        .map_err(|error| Diagnostic::at(number, line, part, error))?;
+    let answer = fields.next()
+        .ok_or_else(|| Diagnostic::at_end(number, line, "expected an answer"))?;
+    if let Some(extra) = fields.next() {
+        return Err(Diagnostic::at(number, line, extra, "unexpected text after the answer"));
+    }
+    Ok(((day, part), String::from(answer)))
+}
+
+impl Manifest {
+    pub fn parse<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Manifest, Error> {
+        let mut diagnostics = Diagnostics::new();
+        let answers = numbered_lines(lines)
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|(number, line)| diagnostics.check(parse_expected(number, line)))
+            .collect();
+        let rejected = diagnostics.into_rejected();
+        if rejected.is_empty() {
+            Ok(Manifest { answers })
+        } else {
+            Err(Error::rejected(rejected))
+        }
+    }
+
+    pub fn read(source: &InputSource) -> Result<Manifest, Error> {
+        source.read_text()
+            .and_then(|text| Manifest::parse(text.lines()))
+            .map_err(|error| source.annotate(error))
+    }
+
+    pub fn expected(&self, day: u8, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Pass,
+    Fail { expected: String, actual: Option<String> },
+    Missing { actual: Option<String> },
+    TimedOut { after: Duration }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |actual: &Option<String>| actual.clone().unwrap_or_else(|| String::from("no answer"));
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail { expected, actual } => write!(f, "FAIL (expected {}, got {})", expected, describe(actual)),
+            Outcome::Missing { actual } => write!(f, "missing (got {})", describe(actual)),
+            Outcome::TimedOut { after } => write!(f, "TIMED OUT (after {:.2?})", after)
+        }
+    }
+}
+
+pub fn check(manifest: &Manifest, day: u8, answer: Answer) -> Outcome {
+    if answer.timed_out {
+        return Outcome::TimedOut { after: answer.duration };
+    }
+    match manifest.expected(day, answer.part) {
+        Some(expected) if answer.value.as_deref() == Some(expected) => Outcome::Pass,
+        Some(expected) => Outcome::Fail { expected: String::from(expected), actual: answer.value },
+        None => Outcome::Missing { actual: answer.value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verify::*;
+
+    fn manifest(lines: &[&str]) -> Result<Manifest, Error> {
+        Manifest::parse(lines.iter().copied())
+    }
+
+    #[test]
+    fn should_parse_manifest_skipping_comments() {
+        let manifest = manifest(&["# day part answer", "", "1 1 100419", "17 2 1180"]).unwrap();
+        assert_eq!(manifest.expected(1, Part::One), Some("100419"));
+        assert_eq!(manifest.expected(17, Part::Two), Some("1180"));
+        assert_eq!(manifest.expected(17, Part::One), None)
+    }
+
+    #[test]
+    fn should_reject_malformed_manifest() {
+        match manifest(&["1 3 100419", "2 1"]) {
+            Err(Error::Parse { diagnostics, .. }) => assert_eq!(
+                diagnostics.iter().map(|it| (it.line, it.column)).collect::<Vec<(usize, usize)>>(),
+                vec!((1, 3), (2, 4))
+            ),
+            _ => panic!("Expected both lines to be rejected.")
+        }
+    }
+
+    #[test]
+    fn should_check_answers() {
+        let manifest = manifest(&["1 1 5"]).unwrap();
+        let answer = |part, value: Option<&str>| Answer {
+            part,
+            value: value.map(String::from),
+            duration: Duration::ZERO,
+            timed_out: false,
+            memory: None
+        };
+        assert_eq!(check(&manifest, 1, answer(Part::One, Some("5"))), Outcome::Pass);
+        assert_eq!(
+            check(&manifest, 1, answer(Part::One, None)),
+            Outcome::Fail { expected: String::from("5"), actual: None }
+        );
+        assert_eq!(
+            check(&manifest, 1, answer(Part::Two, Some("7"))),
+            Outcome::Missing { actual: Some(String::from("7")) }
+        );
+        let timed_out = Answer { part: Part::One, value: None, duration: Duration::from_secs(1), timed_out: true, memory: None };
+        assert_eq!(check(&manifest, 1, timed_out), Outcome::TimedOut { after: Duration::from_secs(1) })
+    }
+}